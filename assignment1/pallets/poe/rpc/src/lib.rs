@@ -0,0 +1,132 @@
+//! RPC interface for the proof of existence pallet.
+//!
+//! A node whose runtime implements `PoeRuntimeApi` adds these methods in its `rpc.rs`
+//! `create_full`, next to the other extensions:
+//!
+//! ```ignore
+//! C::Api: pallet_poe_rpc::PoeRuntimeApi<Block, AccountId, BlockNumber, Balance>,
+//! ...
+//! io.extend_with(pallet_poe_rpc::PoeApi::to_delegate(pallet_poe_rpc::Poe::new(client.clone())));
+//! ```
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::H256;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_poe_runtime_api::PoeApi as PoeRuntimeApi;
+use pallet_poe_runtime_api::{ClaimInfo, Proof, ProofDigest};
+
+/// Proof of existence RPC methods.
+#[rpc]
+pub trait PoeApi<BlockHash, AccountId, BlockNumber, Balance> {
+    /// The owner, creation and update blocks and deposit of the claim on `proof`.
+    #[rpc(name = "poe_claimOf")]
+    fn claim_of(
+        &self,
+        proof: Proof,
+        at: Option<BlockHash>,
+    ) -> Result<Option<ClaimInfo<AccountId, BlockNumber, Balance>>>;
+
+    /// The digests of all claims owned by `owner`.
+    #[rpc(name = "poe_claimsOf")]
+    fn claims_of(&self, owner: AccountId, at: Option<BlockHash>) -> Result<Vec<ProofDigest>>;
+
+    /// The total number of claims.
+    #[rpc(name = "poe_claimCount")]
+    fn claim_count(&self, at: Option<BlockHash>) -> Result<u32>;
+
+    /// Check a Merkle inclusion proof of `leaf` against an anchored `root`.
+    ///
+    /// See `pallet_poe::merkle` for the tree format.
+    #[rpc(name = "poe_verifyInclusion")]
+    fn verify_inclusion(
+        &self,
+        root: H256,
+        leaf: H256,
+        proof: Vec<H256>,
+        at: Option<BlockHash>,
+    ) -> Result<bool>;
+}
+
+/// An implementation of proof of existence specific RPC methods.
+pub struct Poe<C, B> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Poe<C, B> {
+    /// Create a new `Poe` with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Poe { client, _marker: Default::default() }
+    }
+}
+
+/// Error code for failures inside the runtime API call.
+const RUNTIME_ERROR: i64 = 1;
+
+fn runtime_error(e: impl std::fmt::Debug) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(RUNTIME_ERROR),
+        message: "Unable to query the proof of existence pallet.".into(),
+        data: Some(format!("{:?}", e).into()),
+    }
+}
+
+impl<C, Block, AccountId, BlockNumber, Balance>
+    PoeApi<<Block as BlockT>::Hash, AccountId, BlockNumber, Balance> for Poe<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static,
+    C: ProvideRuntimeApi<Block>,
+    C: HeaderBackend<Block>,
+    C::Api: PoeRuntimeApi<Block, AccountId, BlockNumber, Balance>,
+    AccountId: Codec,
+    BlockNumber: Codec,
+    Balance: Codec,
+{
+    fn claim_of(
+        &self,
+        proof: Proof,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<ClaimInfo<AccountId, BlockNumber, Balance>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.claim_of(&at, proof).map_err(runtime_error)
+    }
+
+    fn claims_of(&self, owner: AccountId, at: Option<<Block as BlockT>::Hash>) -> Result<Vec<ProofDigest>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.claims_of(&at, owner).map_err(runtime_error)
+    }
+
+    fn claim_count(&self, at: Option<<Block as BlockT>::Hash>) -> Result<u32> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.claim_count(&at).map_err(runtime_error)
+    }
+
+    fn verify_inclusion(
+        &self,
+        root: H256,
+        leaf: H256,
+        proof: Vec<H256>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<bool> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let proof = proof.into_iter().map(|node| node.to_fixed_bytes()).collect();
+
+        api.verify_inclusion(&at, root.to_fixed_bytes(), leaf.to_fixed_bytes(), proof)
+            .map_err(runtime_error)
+    }
+}
@@ -0,0 +1,28 @@
+//! Runtime API definition for the proof of existence pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+pub use pallet_poe::{ClaimInfo, HashAlgorithm, Proof, ProofDigest};
+
+sp_api::decl_runtime_apis! {
+    /// Queries over the claims stored by the proof of existence pallet.
+    pub trait PoeApi<AccountId, BlockNumber, Balance> where
+        AccountId: Codec,
+        BlockNumber: Codec,
+        Balance: Codec,
+    {
+        /// The claim on `proof`, if any.
+        fn claim_of(proof: Proof) -> Option<ClaimInfo<AccountId, BlockNumber, Balance>>;
+        /// The digests of all claims owned by `owner`.
+        fn claims_of(owner: AccountId) -> Vec<ProofDigest>;
+        /// The total number of claims.
+        fn claim_count() -> u32;
+        /// Whether `proof` shows that `leaf` belongs to the anchored Merkle `root`.
+        fn verify_inclusion(root: [u8; 32], leaf: [u8; 32], proof: Vec<[u8; 32]>) -> bool;
+        /// The accounts that co-signed `claim`, in no particular order.
+        fn attestations_of(claim: ProofDigest) -> Vec<AccountId>;
+    }
+}
@@ -0,0 +1,304 @@
+//! Proof of existence pallet benchmarking.
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+
+use frame_benchmarking::{benchmarks, account, whitelisted_caller};
+use frame_system::RawOrigin;
+use sp_runtime::traits::Bounded;
+use sp_std::prelude::*;
+
+const SEED: u32 = 0;
+
+fn fund<T: Trait>(who: &T::AccountId) {
+    T::Currency::make_free_balance_be(who, BalanceOf::<T>::max_value() / 2u32.into());
+}
+
+fn funded_caller<T: Trait>() -> T::AccountId {
+    let caller: T::AccountId = whitelisted_caller();
+    fund::<T>(&caller);
+    caller
+}
+
+fn funded_account<T: Trait>(name: &'static str, index: u32) -> T::AccountId {
+    let who: T::AccountId = account(name, index, SEED);
+    fund::<T>(&who);
+    who
+}
+
+/// A document of `len` bytes that differs from the documents of other `index` values.
+fn document(index: u32, len: u32) -> Proof {
+    let bytes = index.to_le_bytes().iter().cycle().take(len as usize).cloned().collect();
+    Proof::Document(HashAlgorithm::Blake2_256, bytes)
+}
+
+/// `n` distinct documents sharing a total of roughly `l` bytes.
+fn documents(n: u32, l: u32) -> Vec<Proof> {
+    let len = (l / n).max(1);
+    (0..n).map(|i| document(i, len)).collect()
+}
+
+/// Give `owner` `count` claims on documents unlike those of `document`'s small indices, so the
+/// benchmarked call works against a full owner index.
+fn prefill_claims<T: Trait>(owner: &T::AccountId, count: u32) -> Result<(), &'static str> {
+    for i in 0..count {
+        Module::<T>::do_create_claim(owner, &document(u32::max_value() - i, 32), None, None)?;
+    }
+    Ok(())
+}
+
+fn filled_metadata(len: u32) -> ClaimMetadata {
+    ClaimMetadata {
+        mime_type: vec![b'm'; len as usize],
+        file_size: Some(u64::max_value()),
+        title: vec![b't'; len as usize],
+        uri: vec![b'u'; len as usize],
+    }
+}
+
+/// The encoded size of metadata with every byte field `len` bytes long.
+fn metadata_size(len: u32) -> u32 {
+    filled_metadata(len).encoded_size() as u32
+}
+
+/// Metadata whose encoding is at least `size` bytes, the unit the dispatch weights are charged
+/// in, spread over the byte fields in turn.
+fn metadata<T: Trait>(size: u32) -> ClaimMetadata {
+    let max_len = T::MetadataMaxLen::get() as usize;
+    let mut metadata = filled_metadata(0);
+    let mut field = 0;
+    while (metadata.encoded_size() as u32) < size {
+        let bytes = match field % 3 {
+            0 => &mut metadata.mime_type,
+            1 => &mut metadata.title,
+            _ => &mut metadata.uri,
+        };
+        // Fields are filled in turn, so once one is full they all are.
+        if bytes.len() == max_len {
+            break;
+        }
+        bytes.push(b'x');
+        field += 1;
+    }
+    metadata
+}
+
+benchmarks! {
+    _ { }
+
+    create_claim {
+        let l in 1 .. T::ProofMaxLen::get() as u32;
+        let m in metadata_size(0) .. metadata_size(T::MetadataMaxLen::get());
+        let caller = funded_caller::<T>();
+        prefill_claims::<T>(&caller, T::MaxClaimsPerAccount::get() - 1)?;
+        let proof = document(0, l);
+        let expires_at = frame_system::Module::<T>::block_number() + 10u32.into();
+    }: _(RawOrigin::Signed(caller), proof.clone(), Some(expires_at), Some(metadata::<T>(m)))
+    verify {
+        assert!(Proofs::<T>::contains_key(&proof.digest()));
+        assert_eq!(Module::<T>::metadata_of(&proof.digest()), Some(metadata::<T>(m)));
+    }
+
+    revoke_claim {
+        let l in 1 .. T::ProofMaxLen::get() as u32;
+        let caller = funded_caller::<T>();
+        prefill_claims::<T>(&caller, T::MaxClaimsPerAccount::get() - 1)?;
+        let proof = document(0, l);
+        Module::<T>::do_create_claim(&caller, &proof, None, None)?;
+        // Every attester's deposit is returned along with the claim.
+        for i in 0 .. T::MaxAttestations::get() {
+            let attester = funded_account::<T>("attester", i);
+            Module::<T>::attest(RawOrigin::Signed(attester).into(), proof.clone())?;
+        }
+    }: _(RawOrigin::Signed(caller), proof.clone())
+    verify {
+        assert!(!Proofs::<T>::contains_key(&proof.digest()));
+    }
+
+    anchor_root {
+        let m in metadata_size(0) .. metadata_size(T::MetadataMaxLen::get());
+        let caller = funded_caller::<T>();
+        prefill_claims::<T>(&caller, T::MaxClaimsPerAccount::get() - 1)?;
+        let root = [1u8; 32];
+        let expires_at = frame_system::Module::<T>::block_number() + 10u32.into();
+    }: _(RawOrigin::Signed(caller), root, Some(expires_at), Some(metadata::<T>(m)))
+    verify {
+        assert_eq!(Module::<T>::claim_kind(&Module::<T>::root_digest(root)), ClaimKind::MerkleRoot);
+    }
+
+    create_claims {
+        // A batch larger than the account can hold would measure a less than full account.
+        let n in 1 .. T::MaxBatchSize::get().min(T::MaxClaimsPerAccount::get());
+        let l in 1 .. T::ProofMaxLen::get() as u32;
+        let caller = funded_caller::<T>();
+        prefill_claims::<T>(&caller, T::MaxClaimsPerAccount::get() - n)?;
+        let proofs = documents(n, l);
+    }: _(RawOrigin::Signed(caller), proofs.clone(), false)
+    verify {
+        assert!(proofs.iter().all(|proof| Proofs::<T>::contains_key(&proof.digest())));
+    }
+
+    revoke_claims {
+        // A batch larger than the account can hold would measure a less than full account.
+        let n in 1 .. T::MaxBatchSize::get().min(T::MaxClaimsPerAccount::get());
+        let l in 1 .. T::ProofMaxLen::get() as u32;
+        let caller = funded_caller::<T>();
+        prefill_claims::<T>(&caller, T::MaxClaimsPerAccount::get() - n)?;
+        let proofs = documents(n, l);
+        for proof in &proofs {
+            Module::<T>::do_create_claim(&caller, proof, None, None)?;
+        }
+    }: _(RawOrigin::Signed(caller), proofs.clone(), false)
+    verify {
+        assert!(proofs.iter().all(|proof| !Proofs::<T>::contains_key(&proof.digest())));
+    }
+
+    offer_claim {
+        let l in 1 .. T::ProofMaxLen::get() as u32;
+        let caller = funded_caller::<T>();
+        let recipient: T::AccountId = account("recipient", 0, SEED);
+        let proof = document(0, l);
+        Module::<T>::do_create_claim(&caller, &proof, None, None)?;
+    }: _(RawOrigin::Signed(caller), proof.clone(), recipient.clone())
+    verify {
+        assert_eq!(Module::<T>::pending_transfer(&proof.digest()), Some(recipient));
+    }
+
+    accept_claim {
+        let l in 1 .. T::ProofMaxLen::get() as u32;
+        let owner = funded_account::<T>("owner", 0);
+        let caller = funded_caller::<T>();
+        prefill_claims::<T>(&caller, T::MaxClaimsPerAccount::get() - 1)?;
+        let proof = document(0, l);
+        Module::<T>::do_create_claim(&owner, &proof, None, None)?;
+        Module::<T>::offer_claim(RawOrigin::Signed(owner).into(), proof.clone(), caller.clone())?;
+    }: _(RawOrigin::Signed(caller.clone()), proof.clone())
+    verify {
+        assert_eq!(Module::<T>::proofs(&proof.digest()).map(|claim| claim.owner), Some(caller));
+    }
+
+    cancel_offer {
+        let l in 1 .. T::ProofMaxLen::get() as u32;
+        let caller = funded_caller::<T>();
+        let recipient: T::AccountId = account("recipient", 0, SEED);
+        let proof = document(0, l);
+        Module::<T>::do_create_claim(&caller, &proof, None, None)?;
+        Module::<T>::offer_claim(RawOrigin::Signed(caller.clone()).into(), proof.clone(), recipient)?;
+    }: _(RawOrigin::Signed(caller), proof.clone())
+    verify {
+        assert_eq!(Module::<T>::pending_transfer(&proof.digest()), None);
+    }
+
+    approve_operator {
+        let caller = funded_caller::<T>();
+        let operator: T::AccountId = account("operator", 0, SEED);
+    }: _(RawOrigin::Signed(caller.clone()), operator.clone())
+    verify {
+        assert!(Module::<T>::is_operator(&caller, &operator));
+    }
+
+    revoke_operator {
+        let caller = funded_caller::<T>();
+        let operator: T::AccountId = account("operator", 0, SEED);
+        Module::<T>::approve_operator(RawOrigin::Signed(caller.clone()).into(), operator.clone())?;
+    }: _(RawOrigin::Signed(caller.clone()), operator.clone())
+    verify {
+        assert!(!Module::<T>::is_operator(&caller, &operator));
+    }
+
+    attest {
+        let l in 1 .. T::ProofMaxLen::get() as u32;
+        let owner = funded_account::<T>("owner", 0);
+        let proof = document(0, l);
+        Module::<T>::do_create_claim(&owner, &proof, None, None)?;
+        // Fill the list up to one below the limit so the duplicate check scans it all.
+        for i in 1 .. T::MaxAttestations::get() {
+            let attester = funded_account::<T>("attester", i);
+            Module::<T>::attest(RawOrigin::Signed(attester).into(), proof.clone())?;
+        }
+        let caller = funded_caller::<T>();
+    }: _(RawOrigin::Signed(caller.clone()), proof.clone())
+    verify {
+        assert!(Module::<T>::attestations_of(&proof.digest()).contains(&caller));
+    }
+
+    withdraw_attestation {
+        let l in 1 .. T::ProofMaxLen::get() as u32;
+        let owner = funded_account::<T>("owner", 0);
+        let caller = funded_caller::<T>();
+        let proof = document(0, l);
+        Module::<T>::do_create_claim(&owner, &proof, None, None)?;
+        Module::<T>::attest(RawOrigin::Signed(caller.clone()).into(), proof.clone())?;
+        for i in 1 .. T::MaxAttestations::get() {
+            let attester = funded_account::<T>("attester", i);
+            Module::<T>::attest(RawOrigin::Signed(attester).into(), proof.clone())?;
+        }
+    }: _(RawOrigin::Signed(caller.clone()), proof.clone())
+    verify {
+        assert!(!Module::<T>::attestations_of(&proof.digest()).contains(&caller));
+    }
+
+    transfer_claim {
+        let l in 1 .. T::ProofMaxLen::get() as u32;
+        let owner = funded_account::<T>("owner", 0);
+        let new_owner = funded_account::<T>("new_owner", 0);
+        prefill_claims::<T>(&new_owner, T::MaxClaimsPerAccount::get() - 1)?;
+        let proof = document(0, l);
+        Module::<T>::do_create_claim(&owner, &proof, None, None)?;
+    }: _(RawOrigin::Root, proof.clone(), new_owner.clone())
+    verify {
+        assert_eq!(Module::<T>::proofs(&proof.digest()).map(|claim| claim.owner), Some(new_owner));
+    }
+
+    renew_claim {
+        let l in 1 .. T::ProofMaxLen::get() as u32;
+        let caller = funded_caller::<T>();
+        let proof = document(0, l);
+        let now = frame_system::Module::<T>::block_number();
+        Module::<T>::do_create_claim(&caller, &proof, Some(now + 10u32.into()), None)?;
+        let expires_at = now + 20u32.into();
+    }: _(RawOrigin::Signed(caller), proof.clone(), Some(expires_at))
+    verify {
+        assert_eq!(Module::<T>::claim_expiry(&proof.digest()), Some(expires_at));
+    }
+
+    set_metadata {
+        let l in 1 .. T::ProofMaxLen::get() as u32;
+        let m in metadata_size(0) .. metadata_size(T::MetadataMaxLen::get());
+        let caller = funded_caller::<T>();
+        let proof = document(0, l);
+        Module::<T>::do_create_claim(&caller, &proof, None, None)?;
+    }: _(RawOrigin::Signed(caller), proof.clone(), Some(metadata::<T>(m)))
+    verify {
+        assert_eq!(Module::<T>::metadata_of(&proof.digest()), Some(metadata::<T>(m)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_create_claim::<Test>());
+            assert_ok!(test_benchmark_revoke_claim::<Test>());
+            assert_ok!(test_benchmark_anchor_root::<Test>());
+            assert_ok!(test_benchmark_create_claims::<Test>());
+            assert_ok!(test_benchmark_revoke_claims::<Test>());
+            assert_ok!(test_benchmark_offer_claim::<Test>());
+            assert_ok!(test_benchmark_accept_claim::<Test>());
+            assert_ok!(test_benchmark_cancel_offer::<Test>());
+            assert_ok!(test_benchmark_approve_operator::<Test>());
+            assert_ok!(test_benchmark_revoke_operator::<Test>());
+            assert_ok!(test_benchmark_attest::<Test>());
+            assert_ok!(test_benchmark_withdraw_attestation::<Test>());
+            assert_ok!(test_benchmark_transfer_claim::<Test>());
+            assert_ok!(test_benchmark_renew_claim::<Test>());
+            assert_ok!(test_benchmark_set_metadata::<Test>());
+        });
+    }
+}
@@ -1,10 +1,29 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use codec::{Encode, Decode};
 use frame_support::{
-    decl_module, decl_storage, decl_event, decl_error, ensure, StorageMap, dispatch, traits::Get
+    decl_module, decl_storage, decl_event, decl_error, ensure, dispatch,
+    StorageValue, StorageMap, StorageDoubleMap, IterableStorageDoubleMap,
+    storage::with_transaction,
+    traits::{Get, Currency, ReservableCurrency, BalanceStatus},
+    weights::Weight,
+};
+use frame_system::{ensure_signed, ensure_root};
+use sp_runtime::{
+    DispatchError, RuntimeDebug, TransactionOutcome,
+    traits::{Saturating, Zero},
 };
-use frame_system::ensure_signed;
 use sp_std::vec::Vec;
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
+
+pub use weights::WeightInfo;
+
+pub mod merkle;
+pub mod migrations;
+pub mod weights;
+
+mod benchmarking;
 
 #[cfg(test)]
 mod mock;
@@ -12,11 +31,155 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+/// The hash algorithms a proof digest can be computed with.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum HashAlgorithm {
+    Blake2_256,
+    Sha2_256,
+    Keccak256,
+}
+
+impl HashAlgorithm {
+    /// Hash a document with this algorithm.
+    pub fn hash(&self, document: &[u8]) -> [u8; 32] {
+        match self {
+            HashAlgorithm::Blake2_256 => sp_io::hashing::blake2_256(document),
+            HashAlgorithm::Sha2_256 => sp_io::hashing::sha2_256(document),
+            HashAlgorithm::Keccak256 => sp_io::hashing::keccak_256(document),
+        }
+    }
+}
+
+/// The fixed-size key a claim is stored under: a 256-bit digest tagged with its algorithm.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ProofDigest {
+    pub algorithm: HashAlgorithm,
+    pub hash: [u8; 32],
+}
+
+/// A proof as submitted by a caller.
+///
+/// Either the document itself, which is hashed on-chain, or a digest the caller computed
+/// off-chain. Both forms resolve to the same `ProofDigest` and therefore the same claim.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum Proof {
+    /// The raw document together with the algorithm used to hash it.
+    Document(HashAlgorithm, Vec<u8>),
+    /// A pre-computed digest of the document.
+    Digest(ProofDigest),
+}
+
+impl Proof {
+    /// The storage key of this proof.
+    pub fn digest(&self) -> ProofDigest {
+        match self {
+            Proof::Document(algorithm, document) => ProofDigest {
+                algorithm: *algorithm,
+                hash: algorithm.hash(document),
+            },
+            Proof::Digest(digest) => *digest,
+        }
+    }
+
+    /// The number of bytes the caller submitted.
+    pub fn submitted_len(&self) -> usize {
+        match self {
+            Proof::Document(_, document) => document.len(),
+            Proof::Digest(digest) => digest.hash.len(),
+        }
+    }
+}
+
+/// What a claim attests to.
+#[derive(Encode, Decode, Clone, Copy, Default, PartialEq, Eq, RuntimeDebug)]
+pub enum ClaimKind {
+    /// A single document.
+    #[default]
+    Document,
+    /// The root of a Merkle tree of document digests, see the `merkle` module.
+    MerkleRoot,
+}
+
+/// Optional descriptive data attached to a claim.
+///
+/// Empty byte fields are treated as unset.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct ClaimMetadata {
+    /// MIME type of the document, e.g. `application/pdf`.
+    pub mime_type: Vec<u8>,
+    /// Size of the document in bytes.
+    pub file_size: Option<u64>,
+    /// Human-readable title.
+    pub title: Vec<u8>,
+    /// External URI where the document can be found.
+    pub uri: Vec<u8>,
+}
+
+impl ClaimMetadata {
+    /// Whether every field fits within `max_len` bytes.
+    pub fn is_within(&self, max_len: usize) -> bool {
+        self.mime_type.len() <= max_len && self.title.len() <= max_len && self.uri.len() <= max_len
+    }
+}
+
+/// Everything stored about a claim.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct ClaimInfo<AccountId, BlockNumber, Balance> {
+    /// The account that owns the claim.
+    pub owner: AccountId,
+    /// The block the claim was created at.
+    pub created_at: BlockNumber,
+    /// The block the claim was last transferred, renewed or given new metadata at.
+    pub updated_at: BlockNumber,
+    /// The deposit reserved from the owner.
+    pub deposit: Balance,
+}
+
+/// The storage layout versions of this pallet.
+#[derive(Encode, Decode, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+pub enum Releases {
+    /// Claims keyed by the raw document bytes and valued `(owner, block_number)`.
+    #[default]
+    V1,
+    /// Claims keyed by `ProofDigest` and valued `ClaimInfo`.
+    V2,
+}
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+pub type ClaimInfoOf<T> = ClaimInfo<
+    <T as frame_system::Trait>::AccountId,
+    <T as frame_system::Trait>::BlockNumber,
+    BalanceOf<T>,
+>;
+
 /// Configure the pallet by specifying the parameters and types on which it depends.
 pub trait Trait: frame_system::Trait {
     /// Because this pallet emits events, it depends on the runtime's definition of an event.
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
     type ProofMaxLen: Get<u16>;
+    /// The currency in which claim deposits are reserved.
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// The flat deposit reserved for every claim.
+    type ClaimDeposit: Get<BalanceOf<Self>>;
+    /// The deposit reserved per byte of storage a claim or an attestation occupies.
+    type ByteDeposit: Get<BalanceOf<Self>>;
+    /// The maximum number of claims that may expire at a single block.
+    type MaxExpiriesPerBlock: Get<u32>;
+    /// The maximum length of each byte field in `ClaimMetadata`.
+    type MetadataMaxLen: Get<u32>;
+    /// The maximum number of claims a single account may own.
+    type MaxClaimsPerAccount: Get<u32>;
+    /// The maximum number of proofs in a single `create_claims` or `revoke_claims` call.
+    type MaxBatchSize: Get<u32>;
+    /// The maximum number of accounts that may attest a single claim.
+    type MaxAttestations: Get<u32>;
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
 }
 
 // The pallet's runtime storage items.
@@ -24,8 +187,32 @@ pub trait Trait: frame_system::Trait {
 decl_storage! {
     trait Store for Module<T: Trait> as TemplateModule {
         /// The storage item for our proofs.
-        /// It maps a proof to the user who made the claim and when they made it.
-        Proofs get(fn proofs): map hasher(blake2_128_concat) Vec<u8> => (T::AccountId, T::BlockNumber);
+        /// It maps a proof digest to its owner, when it was created and last updated, and its deposit.
+        Proofs get(fn proofs): map hasher(blake2_128_concat) ProofDigest => Option<ClaimInfoOf<T>>;
+        /// The number of entries in `Proofs`.
+        ClaimCount get(fn claim_count): u32;
+        /// The block at which a time-limited claim expires.
+        ClaimExpiry get(fn claim_expiry): map hasher(blake2_128_concat) ProofDigest => Option<T::BlockNumber>;
+        /// The claims due to expire at each block, bounded by `MaxExpiriesPerBlock` and
+        /// consumed by `on_initialize`.
+        ExpiringClaims get(fn expiring_claims): map hasher(twox_64_concat) T::BlockNumber => Vec<ProofDigest>;
+        /// The account each claim has been offered to, awaiting its acceptance.
+        PendingTransfers get(fn pending_transfer): map hasher(blake2_128_concat) ProofDigest => Option<T::AccountId>;
+        /// Descriptive metadata of each claim, if the owner provided any.
+        MetadataOf get(fn metadata_of): map hasher(blake2_128_concat) ProofDigest => Option<ClaimMetadata>;
+        /// The claims owned by each account, listed by `claims_of`.
+        ClaimsOf: double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) ProofDigest => Option<()>;
+        /// The number of claims owned by each account, bounded by `MaxClaimsPerAccount`.
+        ClaimCountOf get(fn claim_count_of): map hasher(blake2_128_concat) T::AccountId => u32;
+        /// The kind of each claim. Only stored for claims that are not plain documents.
+        ClaimKinds get(fn claim_kind): map hasher(blake2_128_concat) ProofDigest => ClaimKind;
+        /// Accounts approved by an owner to revoke and offer its claims on its behalf.
+        Operators get(fn is_operator):
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) T::AccountId => bool;
+        /// The accounts that co-signed each claim, bounded by `MaxAttestations`.
+        Attestations get(fn attestations_of): map hasher(blake2_128_concat) ProofDigest => Vec<T::AccountId>;
+        /// The storage layout version, used to decide which migrations to run on upgrade.
+        StorageVersion build(|_| Releases::V2): Releases;
     }
 }
 
@@ -33,13 +220,46 @@ decl_storage! {
 // Event documentation should end with an array that provides descriptive names for parameters.
 // https://substrate.dev/docs/en/knowledgebase/runtime/events
 decl_event! {
-    pub enum Event<T> where AccountId = <T as frame_system::Trait>::AccountId {
+    pub enum Event<T> where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        BlockNumber = <T as frame_system::Trait>::BlockNumber,
+    {
         /// Event emitted when a proof has been claimed. [who, claim]
-        ClaimCreated(AccountId, Vec<u8>),
+        ClaimCreated(AccountId, ProofDigest),
         /// Event emitted when a claim is revoked by the owner. [who, claim]
-        ClaimRevoked(AccountId, Vec<u8>),
-        /// Event emitted when a claim is transferred by the owner. [who, claim, new_owner]
-        ClaimTransferred(AccountId, Vec<u8>, AccountId),
+        ClaimRevoked(AccountId, ProofDigest),
+        /// Event emitted when a claim is transferred. [old_owner, claim, new_owner]
+        ClaimTransferred(AccountId, ProofDigest, AccountId),
+        /// Event emitted when the owner offers a claim to another account. [who, claim, to]
+        ClaimOffered(AccountId, ProofDigest, AccountId),
+        /// Event emitted when the owner withdraws a pending offer. [who, claim]
+        ClaimOfferCancelled(AccountId, ProofDigest),
+        /// Event emitted when the owner sets or clears the metadata of a claim. [who, claim]
+        MetadataUpdated(AccountId, ProofDigest),
+        /// Event emitted when an owner approves an operator. [owner, operator]
+        OperatorApproved(AccountId, AccountId),
+        /// Event emitted when an owner withdraws an operator's approval. [owner, operator]
+        OperatorRevoked(AccountId, AccountId),
+        /// Event emitted when an operator revokes a claim for its owner. [operator, owner, claim]
+        ClaimRevokedByOperator(AccountId, AccountId, ProofDigest),
+        /// Event emitted when an operator offers a claim for its owner. [operator, owner, claim, to]
+        ClaimOfferedByOperator(AccountId, AccountId, ProofDigest, AccountId),
+        /// Event emitted when an operator withdraws an offer for its owner. [operator, owner, claim]
+        ClaimOfferCancelledByOperator(AccountId, AccountId, ProofDigest),
+        /// Event emitted when an account co-signs a claim. [who, claim]
+        ClaimAttested(AccountId, ProofDigest),
+        /// Event emitted when an account withdraws its co-signature. [who, claim]
+        AttestationWithdrawn(AccountId, ProofDigest),
+        /// Event emitted when a Merkle root of a document batch has been claimed. [who, root]
+        RootAnchored(AccountId, ProofDigest),
+        /// Event emitted when an item of a best-effort batch fails and is skipped. [claim, error]
+        BatchItemFailed(ProofDigest, DispatchError),
+        /// Event emitted when a batch call finishes. [who, succeeded, failed]
+        BatchCompleted(AccountId, u32, u32),
+        /// Event emitted when the owner changes the expiry of a claim. [who, claim, expires_at]
+        ClaimRenewed(AccountId, ProofDigest, Option<BlockNumber>),
+        /// Event emitted when a time-limited claim reaches its expiry block. [owner, claim]
+        ClaimExpired(AccountId, ProofDigest),
     }
 }
 
@@ -50,10 +270,34 @@ decl_error! {
         ProofAlreadyClaimed,
         /// The proof does not exist, so it cannot be revoked.
         NoSuchProof,
-        /// The proof is claimed by another account, so caller can't revoke it.
+        /// The proof is claimed by another account, and the caller is not its operator.
         NotProofOwner,
         /// The proof length is too long
         ProofTooLong,
+        /// The expiry block must be in the future.
+        InvalidExpiry,
+        /// The claim has not been offered to anyone.
+        NoPendingOffer,
+        /// The claim has been offered to another account.
+        NotOfferRecipient,
+        /// A metadata field is longer than `MetadataMaxLen`.
+        MetadataTooLong,
+        /// The account already owns `MaxClaimsPerAccount` claims.
+        TooManyClaims,
+        /// The batch contains more than `MaxBatchSize` proofs.
+        BatchTooLarge,
+        /// The account is not an approved operator of the caller.
+        NotOperator,
+        /// The owner of a claim cannot attest it.
+        CannotAttestOwnClaim,
+        /// The caller has already attested the claim.
+        AlreadyAttested,
+        /// The caller has not attested the claim.
+        NotAttested,
+        /// The claim already has `MaxAttestations` attestations.
+        TooManyAttestations,
+        /// `MaxExpiriesPerBlock` claims already expire at the requested block.
+        TooManyExpiries,
     }
 }
 
@@ -68,88 +312,623 @@ decl_module! {
         // Events must be initialized if they are used by the pallet.
         fn deposit_event() = default;
 
+        /// Migrate storage written by an older version of this pallet.
+        fn on_runtime_upgrade() -> Weight {
+            migrations::migrate_to_v2::<T>()
+        }
+
+        /// Remove the claims that expire at this block.
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            Self::expire_claims(now)
+        }
+
         /// Allow a user to claim ownership of an unclaimed proof.
-        #[weight = 10_000]
-        fn create_claim(origin, proof: Vec<u8>) -> dispatch::DispatchResult {
+        ///
+        /// If `expires_at` is set, the claim is removed once that block is reached.
+        #[weight = T::WeightInfo::create_claim(proof.submitted_len() as u32, Module::<T>::metadata_len(metadata))]
+        fn create_claim(
+            origin,
+            proof: Proof,
+            expires_at: Option<T::BlockNumber>,
+            metadata: Option<ClaimMetadata>,
+        ) -> dispatch::DispatchResult {
             // Check that the extrinsic was signed and get the signer.
             // This function will return an error if the extrinsic is not signed.
             // https://substrate.dev/docs/en/knowledgebase/runtime/origin
             let sender = ensure_signed(origin)?;
 
-            // Verify that the specified proof has not already been claimed.
-            ensure!(!Proofs::<T>::contains_key(&proof), Error::<T>::ProofAlreadyClaimed);
-
-            // Verify the length of proof is valid
-            ensure!(proof.len() <= T::ProofMaxLen::get() as usize, Error::<T>::ProofTooLong);
-            
-            // Get the block number from the FRAME System module.
-            let current_block = <frame_system::Module<T>>::block_number();
-
-            // Store the proof with the sender and block number.
-            Proofs::<T>::insert(&proof, (&sender, current_block));
-
-            // Emit an event that the claim was created.
-            Self::deposit_event(RawEvent::ClaimCreated(sender, proof));
+            Self::do_create_claim(&sender, &proof, expires_at, metadata)?;
 
             Ok(())
         }
 
         /// Allow the owner to revoke their claim.
-        #[weight = 10_000]
-        fn revoke_claim(origin, proof: Vec<u8>) -> dispatch::DispatchResult {
+        #[weight = T::WeightInfo::revoke_claim(proof.submitted_len() as u32)]
+        fn revoke_claim(origin, proof: Proof) -> dispatch::DispatchResult {
             // Check that the extrinsic was signed and get the signer.
             // This function will return an error if the extrinsic is not signed.
             // https://substrate.dev/docs/en/knowledgebase/runtime/origin
             let sender = ensure_signed(origin)?;
 
-            // Verify that the specified proof has been claimed.
-            ensure!(Proofs::<T>::contains_key(&proof), Error::<T>::NoSuchProof);
+            Self::do_revoke_claim(&sender, &proof)?;
 
-            // Get owner of the claim.
-            let (owner, _) = Proofs::<T>::get(&proof);
+            Ok(())
+        }
 
-            // Verify that sender of the current call is the claim owner.
-            ensure!(sender == owner, Error::<T>::NotProofOwner);
+        /// Claim the Merkle root of a batch of documents.
+        ///
+        /// Membership of individual documents can later be checked with `verify_inclusion`.
+        #[weight = T::WeightInfo::anchor_root(Module::<T>::metadata_len(metadata))]
+        fn anchor_root(
+            origin,
+            root: merkle::Hash,
+            expires_at: Option<T::BlockNumber>,
+            metadata: Option<ClaimMetadata>,
+        ) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
 
-            // Remove claim from storage.
-            Proofs::<T>::remove(&proof);
+            let proof = Proof::Digest(Self::root_digest(root));
+            Self::do_create_claim(&sender, &proof, expires_at, metadata)?;
+            ClaimKinds::insert(proof.digest(), ClaimKind::MerkleRoot);
 
-            // Emit an event that the claim was erased.
-            Self::deposit_event(RawEvent::ClaimRevoked(sender, proof));
+            Self::deposit_event(RawEvent::RootAnchored(sender, proof.digest()));
 
             Ok(())
         }
 
-        /// Allow the owner to transfer their claim.
-        #[weight = 10_000]
-        fn transfer_claim(origin, proof: Vec<u8>, new_owner: T::AccountId) -> dispatch::DispatchResult {
-            // Check that the extrinsic was signed and get the signer.
-            // This function will return an error if the extrinsic is not signed.
-            // https://substrate.dev/docs/en/knowledgebase/runtime/origin
+        /// Claim several proofs in one call.
+        ///
+        /// Unless `best_effort` is set the batch is atomic: if any proof cannot be claimed, none
+        /// are. In best-effort mode failing proofs are skipped and reported with `BatchItemFailed`.
+        #[weight = T::WeightInfo::create_claims(proofs.len() as u32, Module::<T>::total_len(proofs))]
+        fn create_claims(origin, proofs: Vec<Proof>, best_effort: bool) -> dispatch::DispatchResult {
             let sender = ensure_signed(origin)?;
 
-            // Verify that the specified proof has been claimed.
-            ensure!(Proofs::<T>::contains_key(&proof), Error::<T>::NoSuchProof);
+            Self::do_batch(&sender, &proofs, best_effort, |proof| {
+                Self::do_create_claim(&sender, proof, None, None)
+            })
+        }
+
+        /// Revoke several claims in one call.
+        ///
+        /// Atomicity follows the same rules as `create_claims`.
+        #[weight = T::WeightInfo::revoke_claims(proofs.len() as u32, Module::<T>::total_len(proofs))]
+        fn revoke_claims(origin, proofs: Vec<Proof>, best_effort: bool) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            Self::do_batch(&sender, &proofs, best_effort, |proof| {
+                Self::do_revoke_claim(&sender, proof)
+            })
+        }
+
+        /// Offer a claim to another account.
+        ///
+        /// Ownership only moves once `to` accepts the offer with `accept_claim`.
+        #[weight = T::WeightInfo::offer_claim(proof.submitted_len() as u32)]
+        fn offer_claim(origin, proof: Proof, to: T::AccountId) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let digest = proof.digest();
+
+            // Verify that the specified proof has been claimed and get its owner.
+            let owner = Self::owner_of(&digest)?;
+
+            // Verify that sender of the current call is the claim owner or its operator.
+            Self::ensure_owner_or_operator(&sender, &owner)?;
+
+            // Replace any earlier offer of the same claim.
+            PendingTransfers::<T>::insert(&digest, &to);
+
+            if sender == owner {
+                Self::deposit_event(RawEvent::ClaimOffered(sender, digest, to));
+            } else {
+                Self::deposit_event(RawEvent::ClaimOfferedByOperator(sender, owner, digest, to));
+            }
+
+            Ok(())
+        }
+
+        /// Accept a claim offered to the sender.
+        #[weight = T::WeightInfo::accept_claim(proof.submitted_len() as u32)]
+        fn accept_claim(origin, proof: Proof) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let digest = proof.digest();
+
+            // Verify that the claim has been offered to the sender.
+            let recipient = PendingTransfers::<T>::get(&digest).ok_or(Error::<T>::NoPendingOffer)?;
+            ensure!(sender == recipient, Error::<T>::NotOfferRecipient);
+
+            let owner = Self::owner_of(&digest)?;
+            Self::do_transfer(&owner, &digest, &sender)?;
+
+            Self::deposit_event(RawEvent::ClaimTransferred(owner, digest, sender));
+
+            Ok(())
+        }
+
+        /// Allow the owner to withdraw a pending offer.
+        #[weight = T::WeightInfo::cancel_offer(proof.submitted_len() as u32)]
+        fn cancel_offer(origin, proof: Proof) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let digest = proof.digest();
+
+            // Verify that the claim has a pending offer.
+            ensure!(PendingTransfers::<T>::contains_key(&digest), Error::<T>::NoPendingOffer);
 
             // Get owner of the claim.
-            let (owner, _) = Proofs::<T>::get(&proof);
+            let owner = Self::owner_of(&digest)?;
 
-            // Verify that sender of the current call is the claim owner.
-            ensure!(sender == owner, Error::<T>::NotProofOwner);
+            // Verify that sender of the current call is the claim owner or its operator.
+            Self::ensure_owner_or_operator(&sender, &owner)?;
+
+            PendingTransfers::<T>::remove(&digest);
+
+            if sender == owner {
+                Self::deposit_event(RawEvent::ClaimOfferCancelled(sender, digest));
+            } else {
+                Self::deposit_event(RawEvent::ClaimOfferCancelledByOperator(sender, owner, digest));
+            }
+
+            Ok(())
+        }
+
+        /// Allow `operator` to revoke, offer and cancel offers of the sender's claims.
+        #[weight = T::WeightInfo::approve_operator()]
+        fn approve_operator(origin, operator: T::AccountId) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            Operators::<T>::insert(&sender, &operator, true);
+
+            Self::deposit_event(RawEvent::OperatorApproved(sender, operator));
+
+            Ok(())
+        }
+
+        /// Withdraw the approval of an operator.
+        #[weight = T::WeightInfo::revoke_operator()]
+        fn revoke_operator(origin, operator: T::AccountId) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(Operators::<T>::contains_key(&sender, &operator), Error::<T>::NotOperator);
+            Operators::<T>::remove(&sender, &operator);
+
+            Self::deposit_event(RawEvent::OperatorRevoked(sender, operator));
+
+            Ok(())
+        }
+
+        /// Co-sign a claim owned by another account, e.g. as a witness or auditor.
+        ///
+        /// Reserves `ByteDeposit` for each byte of the sender's account id, returned when the
+        /// attestation is withdrawn or the claim is removed.
+        #[weight = T::WeightInfo::attest(proof.submitted_len() as u32)]
+        fn attest(origin, proof: Proof) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let digest = proof.digest();
+
+            // Verify that the specified proof has been claimed by someone else.
+            let owner = Self::owner_of(&digest)?;
+            ensure!(sender != owner, Error::<T>::CannotAttestOwnClaim);
+
+            Attestations::<T>::try_mutate(&digest, |attesters| -> dispatch::DispatchResult {
+                ensure!(!attesters.contains(&sender), Error::<T>::AlreadyAttested);
+                ensure!(attesters.len() < T::MaxAttestations::get() as usize, Error::<T>::TooManyAttestations);
+                T::Currency::reserve(&sender, Self::attestation_deposit(&sender))?;
+                attesters.push(sender.clone());
+                Ok(())
+            })?;
+
+            Self::deposit_event(RawEvent::ClaimAttested(sender, digest));
+
+            Ok(())
+        }
+
+        /// Withdraw the sender's co-signature from a claim.
+        #[weight = T::WeightInfo::withdraw_attestation(proof.submitted_len() as u32)]
+        fn withdraw_attestation(origin, proof: Proof) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let digest = proof.digest();
+
+            Attestations::<T>::try_mutate_exists(&digest, |maybe_attesters| -> dispatch::DispatchResult {
+                let attesters = maybe_attesters.as_mut().ok_or(Error::<T>::NotAttested)?;
+                let index = attesters.iter().position(|a| a == &sender).ok_or(Error::<T>::NotAttested)?;
+                attesters.swap_remove(index);
+                if attesters.is_empty() {
+                    *maybe_attesters = None;
+                }
+                Ok(())
+            })?;
+            T::Currency::unreserve(&sender, Self::attestation_deposit(&sender));
+
+            Self::deposit_event(RawEvent::AttestationWithdrawn(sender, digest));
+
+            Ok(())
+        }
+
+        /// Move a claim to a new owner without their consent.
+        ///
+        /// The dispatch origin for this call must be _Root_.
+        #[weight = T::WeightInfo::transfer_claim(proof.submitted_len() as u32)]
+        fn transfer_claim(origin, proof: Proof, new_owner: T::AccountId) -> dispatch::DispatchResult {
+            ensure_root(origin)?;
+
+            let digest = proof.digest();
 
-            // Remove claim from storage.
-            Proofs::<T>::remove(&proof);
+            // Verify that the specified proof has been claimed and get its owner.
+            let owner = Self::owner_of(&digest)?;
 
-            // Get the block number from the FRAME System module.
+            Self::do_transfer(&owner, &digest, &new_owner)?;
+
+            // Emit an event that the claim was transferred.
+            Self::deposit_event(RawEvent::ClaimTransferred(owner, digest, new_owner));
+
+            Ok(())
+        }
+
+        /// Allow the owner to change when their claim expires.
+        ///
+        /// Passing `None` makes the claim permanent.
+        #[weight = T::WeightInfo::renew_claim(proof.submitted_len() as u32)]
+        fn renew_claim(origin, proof: Proof, expires_at: Option<T::BlockNumber>) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let digest = proof.digest();
+
+            // Verify that the specified proof has been claimed.
+            let mut claim = Proofs::<T>::get(&digest).ok_or(Error::<T>::NoSuchProof)?;
+
+            // Verify that sender of the current call is the claim owner.
+            ensure!(sender == claim.owner, Error::<T>::NotProofOwner);
+
+            // Verify that the claim does not expire in the past.
             let current_block = <frame_system::Module<T>>::block_number();
+            Self::ensure_valid_expiry(&digest, expires_at, current_block)?;
+
+            // Replace the previous expiry, if any.
+            Self::clear_expiry(&digest);
+            if let Some(at) = expires_at {
+                Self::set_expiry(&digest, at);
+            }
 
-            // Store the proof with the sender and block number.
-            Proofs::<T>::insert(&proof, (&new_owner, current_block));
+            claim.updated_at = current_block;
+            Proofs::<T>::insert(&digest, claim);
 
-            // Emit an event that the claim was created.
-            Self::deposit_event(RawEvent::ClaimTransferred(sender, proof, new_owner));
+            Self::deposit_event(RawEvent::ClaimRenewed(sender, digest, expires_at));
 
             Ok(())
         }
+
+        /// Allow the owner to replace the metadata of their claim.
+        ///
+        /// Passing `None` removes the metadata. The deposit is adjusted to the new size.
+        #[weight = T::WeightInfo::set_metadata(proof.submitted_len() as u32, Module::<T>::metadata_len(metadata))]
+        fn set_metadata(origin, proof: Proof, metadata: Option<ClaimMetadata>) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            // Verify the length of metadata is valid
+            Self::ensure_valid_metadata(&metadata)?;
+
+            let digest = proof.digest();
+
+            // Verify that the specified proof has been claimed.
+            let mut claim = Proofs::<T>::get(&digest).ok_or(Error::<T>::NoSuchProof)?;
+
+            // Verify that sender of the current call is the claim owner.
+            ensure!(sender == claim.owner, Error::<T>::NotProofOwner);
+
+            // Top up or refund the deposit for the new metadata size.
+            let new_deposit = Self::deposit_for(&digest, metadata.as_ref());
+            if new_deposit > claim.deposit {
+                T::Currency::reserve(&sender, new_deposit - claim.deposit)?;
+            } else {
+                T::Currency::unreserve(&sender, claim.deposit - new_deposit);
+            }
+            claim.deposit = new_deposit;
+            claim.updated_at = <frame_system::Module<T>>::block_number();
+            Proofs::<T>::insert(&digest, claim);
+
+            match metadata {
+                Some(metadata) => MetadataOf::insert(&digest, metadata),
+                None => MetadataOf::remove(&digest),
+            }
+
+            Self::deposit_event(RawEvent::MetadataUpdated(sender, digest));
+
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// The deposit required to store a claim under `digest` with the given metadata.
+    pub fn deposit_for(digest: &ProofDigest, metadata: Option<&ClaimMetadata>) -> BalanceOf<T> {
+        let size = digest.encoded_size() + metadata.map_or(0, |m| m.encoded_size());
+        let bytes = BalanceOf::<T>::from(size as u32);
+        T::ClaimDeposit::get().saturating_add(T::ByteDeposit::get().saturating_mul(bytes))
+    }
+
+    fn owner_of(digest: &ProofDigest) -> Result<T::AccountId, DispatchError> {
+        Proofs::<T>::get(digest).map(|claim| claim.owner).ok_or_else(|| Error::<T>::NoSuchProof.into())
+    }
+
+    fn ensure_owner_or_operator(who: &T::AccountId, owner: &T::AccountId) -> dispatch::DispatchResult {
+        ensure!(who == owner || Operators::<T>::get(owner, who), Error::<T>::NotProofOwner);
+        Ok(())
+    }
+
+    /// The digests of the claims owned by `owner`, in no particular order.
+    pub fn claims_of(owner: &T::AccountId) -> Vec<ProofDigest> {
+        ClaimsOf::<T>::iter_prefix(owner).map(|(digest, ())| digest).collect()
+    }
+
+    fn ensure_can_own_more(who: &T::AccountId) -> dispatch::DispatchResult {
+        ensure!(ClaimCountOf::<T>::get(who) < T::MaxClaimsPerAccount::get(), Error::<T>::TooManyClaims);
+        Ok(())
+    }
+
+    fn add_to_owner(owner: &T::AccountId, digest: &ProofDigest) {
+        ClaimsOf::<T>::insert(owner, digest, ());
+        ClaimCountOf::<T>::mutate(owner, |count| *count = count.saturating_add(1));
+    }
+
+    fn remove_from_owner(owner: &T::AccountId, digest: &ProofDigest) {
+        if ClaimsOf::<T>::take(owner, digest).is_some() {
+            ClaimCountOf::<T>::mutate_exists(owner, |count| {
+                *count = count.map(|count| count.saturating_sub(1)).filter(|count| *count > 0);
+            });
+        }
+    }
+
+    fn ensure_valid_metadata(metadata: &Option<ClaimMetadata>) -> dispatch::DispatchResult {
+        if let Some(metadata) = metadata {
+            ensure!(metadata.is_within(T::MetadataMaxLen::get() as usize), Error::<T>::MetadataTooLong);
+        }
+        Ok(())
+    }
+
+    fn ensure_valid_expiry(
+        digest: &ProofDigest,
+        expires_at: Option<T::BlockNumber>,
+        now: T::BlockNumber,
+    ) -> dispatch::DispatchResult {
+        if let Some(at) = expires_at {
+            ensure!(at > now, Error::<T>::InvalidExpiry);
+            // A claim renewed to the block it already expires at keeps its place.
+            if ClaimExpiry::<T>::get(digest) != Some(at) {
+                let due = ExpiringClaims::<T>::decode_len(at).unwrap_or(0);
+                ensure!(due < T::MaxExpiriesPerBlock::get() as usize, Error::<T>::TooManyExpiries);
+            }
+        }
+        Ok(())
+    }
+
+    fn set_expiry(digest: &ProofDigest, at: T::BlockNumber) {
+        ClaimExpiry::<T>::insert(digest, at);
+        ExpiringClaims::<T>::append(at, digest);
+    }
+
+    fn clear_expiry(digest: &ProofDigest) {
+        if let Some(at) = ClaimExpiry::<T>::take(digest) {
+            ExpiringClaims::<T>::mutate_exists(at, |maybe_due| {
+                if let Some(due) = maybe_due {
+                    due.retain(|d| d != digest);
+                    if due.is_empty() {
+                        *maybe_due = None;
+                    }
+                }
+            });
+        }
+    }
+
+    /// The deposit reserved from `attester` for its entry in a claim's attestations.
+    pub fn attestation_deposit(attester: &T::AccountId) -> BalanceOf<T> {
+        let bytes = BalanceOf::<T>::from(attester.encoded_size() as u32);
+        T::ByteDeposit::get().saturating_mul(bytes)
+    }
+
+    /// The number of bytes of metadata submitted with a call.
+    pub fn metadata_len(metadata: &Option<ClaimMetadata>) -> u32 {
+        metadata.as_ref().map_or(0, |m| m.encoded_size() as u32)
+    }
+
+    /// The total number of bytes submitted in a batch.
+    pub fn total_len(proofs: &[Proof]) -> u32 {
+        proofs.iter().fold(0u32, |total, proof| total.saturating_add(proof.submitted_len() as u32))
+    }
+
+    /// Apply `f` to every proof, either atomically or skipping the proofs it fails on.
+    fn do_batch<F>(sender: &T::AccountId, proofs: &[Proof], best_effort: bool, f: F) -> dispatch::DispatchResult
+        where F: Fn(&Proof) -> dispatch::DispatchResult
+    {
+        ensure!(proofs.len() <= T::MaxBatchSize::get() as usize, Error::<T>::BatchTooLarge);
+
+        let mut failed = 0u32;
+        if best_effort {
+            for proof in proofs {
+                // Roll back each failing item on its own so it leaves no partial state behind.
+                let result = with_transaction(|| match f(proof) {
+                    Ok(()) => TransactionOutcome::Commit(Ok(())),
+                    Err(e) => TransactionOutcome::Rollback(Err(e)),
+                });
+                if let Err(e) = result {
+                    failed += 1;
+                    Self::deposit_event(RawEvent::BatchItemFailed(proof.digest(), e));
+                }
+            }
+        } else {
+            with_transaction(|| {
+                for proof in proofs {
+                    if let Err(e) = f(proof) {
+                        return TransactionOutcome::Rollback(Err(e));
+                    }
+                }
+                TransactionOutcome::Commit(Ok(()))
+            })?;
+        }
+
+        let succeeded = proofs.len() as u32 - failed;
+        Self::deposit_event(RawEvent::BatchCompleted(sender.clone(), succeeded, failed));
+
+        Ok(())
+    }
+
+    fn do_create_claim(
+        sender: &T::AccountId,
+        proof: &Proof,
+        expires_at: Option<T::BlockNumber>,
+        metadata: Option<ClaimMetadata>,
+    ) -> dispatch::DispatchResult {
+        // Verify the length of proof is valid
+        ensure!(proof.submitted_len() <= T::ProofMaxLen::get() as usize, Error::<T>::ProofTooLong);
+
+        // Verify the length of metadata is valid
+        Self::ensure_valid_metadata(&metadata)?;
+
+        let digest = proof.digest();
+
+        // Verify that the specified proof has not already been claimed.
+        ensure!(!Proofs::<T>::contains_key(&digest), Error::<T>::ProofAlreadyClaimed);
+
+        // Get the block number from the FRAME System module.
+        let current_block = <frame_system::Module<T>>::block_number();
+
+        // Verify that the claim does not expire in the past.
+        Self::ensure_valid_expiry(&digest, expires_at, current_block)?;
+
+        // Verify that the sender can own another claim.
+        Self::ensure_can_own_more(sender)?;
+
+        // Reserve the storage deposit from the sender.
+        let deposit = Self::deposit_for(&digest, metadata.as_ref());
+        T::Currency::reserve(sender, deposit)?;
+
+        // Store the proof with the sender, block number and deposit.
+        Proofs::<T>::insert(&digest, ClaimInfo {
+            owner: sender.clone(),
+            created_at: current_block,
+            updated_at: current_block,
+            deposit,
+        });
+        ClaimCount::mutate(|count| *count = count.saturating_add(1));
+        Self::add_to_owner(sender, &digest);
+        if let Some(at) = expires_at {
+            Self::set_expiry(&digest, at);
+        }
+        if let Some(metadata) = metadata {
+            MetadataOf::insert(&digest, metadata);
+        }
+
+        // Emit an event that the claim was created.
+        Self::deposit_event(RawEvent::ClaimCreated(sender.clone(), digest));
+
+        Ok(())
+    }
+
+    fn do_revoke_claim(sender: &T::AccountId, proof: &Proof) -> dispatch::DispatchResult {
+        let digest = proof.digest();
+
+        // Verify that the specified proof has been claimed and get its owner.
+        let owner = Self::owner_of(&digest)?;
+
+        // Verify that sender of the current call is the claim owner or its operator.
+        Self::ensure_owner_or_operator(sender, &owner)?;
+
+        // Remove claim from storage and return the deposit.
+        Self::remove_claim(&owner, &digest);
+
+        // Emit an event that the claim was erased.
+        if *sender == owner {
+            Self::deposit_event(RawEvent::ClaimRevoked(owner, digest));
+        } else {
+            Self::deposit_event(RawEvent::ClaimRevokedByOperator(sender.clone(), owner, digest));
+        }
+
+        Ok(())
+    }
+
+    /// Remove a claim and everything stored alongside it, returning the deposit to `owner`.
+    fn remove_claim(owner: &T::AccountId, digest: &ProofDigest) {
+        if let Some(claim) = Proofs::<T>::take(digest) {
+            T::Currency::unreserve(owner, claim.deposit);
+            ClaimCount::mutate(|count| *count = count.saturating_sub(1));
+        }
+        Self::remove_from_owner(owner, digest);
+        PendingTransfers::<T>::remove(digest);
+        MetadataOf::remove(digest);
+        ClaimKinds::remove(digest);
+        for attester in Attestations::<T>::take(digest) {
+            T::Currency::unreserve(&attester, Self::attestation_deposit(&attester));
+        }
+        Self::clear_expiry(digest);
+    }
+
+    /// Reassign a claim to `new_owner`, moving its reserved deposit along with it.
+    fn do_transfer(owner: &T::AccountId, digest: &ProofDigest, new_owner: &T::AccountId) -> dispatch::DispatchResult {
+        Self::ensure_can_own_more(new_owner)?;
+
+        let mut claim = Proofs::<T>::get(digest).ok_or(Error::<T>::NoSuchProof)?;
+        if !claim.deposit.is_zero() {
+            let not_moved = T::Currency::repatriate_reserved(owner, new_owner, claim.deposit, BalanceStatus::Reserved)?;
+            // Whatever could not be moved is no longer tracked by the claim, so release it rather
+            // than leaving it reserved on the old owner.
+            T::Currency::unreserve(owner, not_moved);
+            claim.deposit = claim.deposit.saturating_sub(not_moved);
+        }
+
+        // Store the proof with the new owner and the block it changed hands at.
+        claim.owner = new_owner.clone();
+        claim.updated_at = <frame_system::Module<T>>::block_number();
+        Proofs::<T>::insert(digest, claim);
+        PendingTransfers::<T>::remove(digest);
+        Self::remove_from_owner(owner, digest);
+        Self::add_to_owner(new_owner, digest);
+
+        Ok(())
+    }
+
+    /// Remove the claims due at `now`, of which there are at most `MaxExpiriesPerBlock`.
+    fn expire_claims(now: T::BlockNumber) -> Weight {
+        let due = ExpiringClaims::<T>::take(now);
+        if due.is_empty() {
+            return T::DbWeight::get().reads(1);
+        }
+
+        let count = due.len() as Weight;
+        // Each removed claim also returns the deposits of up to `MaxAttestations` attesters.
+        let attesters = T::MaxAttestations::get() as Weight;
+        for digest in due {
+            let owner = match Proofs::<T>::get(&digest) {
+                Some(claim) => claim.owner,
+                None => continue,
+            };
+            Self::remove_claim(&owner, &digest);
+            Self::deposit_event(RawEvent::ClaimExpired(owner, digest));
+        }
+
+        T::DbWeight::get().reads_writes(2 + (3 + attesters) * count, 2 + (4 + attesters) * count)
+    }
+
+    /// The key a Merkle root claim is stored under.
+    pub fn root_digest(root: merkle::Hash) -> ProofDigest {
+        ProofDigest { algorithm: HashAlgorithm::Blake2_256, hash: root }
+    }
+
+    /// Whether `leaf` is included in the anchored Merkle `root`.
+    ///
+    /// Returns `false` if `root` has not been anchored with `anchor_root`.
+    pub fn verify_inclusion(root: merkle::Hash, leaf: merkle::Hash, proof: Vec<merkle::Hash>) -> bool {
+        let digest = Self::root_digest(root);
+        Proofs::<T>::contains_key(&digest)
+            && ClaimKinds::get(&digest) == ClaimKind::MerkleRoot
+            && merkle::verify(&root, &leaf, &proof)
+    }
+
+    /// Look up a claim by either the document or its digest.
+    pub fn claim_of(proof: &Proof) -> Option<ClaimInfoOf<T>> {
+        Proofs::<T>::get(&proof.digest())
     }
 }
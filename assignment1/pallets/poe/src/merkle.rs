@@ -0,0 +1,90 @@
+//! Binary Merkle trees for anchoring document batches under a single root claim.
+//!
+//! The tree format is fixed so that off-chain tools can build roots and proofs that this
+//! pallet verifies:
+//!
+//! - Leaves are 32-byte document digests, in the order chosen by whoever builds the batch.
+//! - A leaf node is `blake2_256(0x00 ++ leaf)`.
+//! - An inner node is `blake2_256(0x01 ++ min(a, b) ++ max(a, b))` where `a` and `b` are its
+//!   children compared as byte strings. Sorting the pair means a proof needs no left/right flags.
+//! - If a level has an odd number of nodes, the last one is carried up to the next level as is.
+//! - A proof is the list of sibling nodes from the leaf level up to, but excluding, the root.
+//!
+//! The prefixes keep leaf and inner nodes apart, so an inner node can never be presented as a
+//! leaf.
+
+use sp_io::hashing::blake2_256;
+use sp_std::vec::Vec;
+
+/// A node of the tree.
+pub type Hash = [u8; 32];
+
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// The node a leaf is stored as.
+pub fn leaf_hash(leaf: &Hash) -> Hash {
+    let mut data = [0u8; 33];
+    data[0] = LEAF_PREFIX;
+    data[1..].copy_from_slice(leaf);
+    blake2_256(&data)
+}
+
+/// The parent of two nodes.
+pub fn node_hash(a: &Hash, b: &Hash) -> Hash {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    let mut data = [0u8; 65];
+    data[0] = NODE_PREFIX;
+    data[1..33].copy_from_slice(low);
+    data[33..].copy_from_slice(high);
+    blake2_256(&data)
+}
+
+/// The next level up from `level`.
+fn parent_level(level: &[Hash]) -> Vec<Hash> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [a, b] => node_hash(a, b),
+            [a] => *a,
+            _ => unreachable!("chunks(2) yields one or two items; qed"),
+        })
+        .collect()
+}
+
+/// The root of the tree over `leaves`, or `None` if there are no leaves.
+pub fn root(leaves: &[Hash]) -> Option<Hash> {
+    let mut level: Vec<Hash> = leaves.iter().map(leaf_hash).collect();
+    if level.is_empty() {
+        return None;
+    }
+    while level.len() > 1 {
+        level = parent_level(&level);
+    }
+    Some(level[0])
+}
+
+/// The inclusion proof of the leaf at `index`, or `None` if `index` is out of range.
+pub fn proof(leaves: &[Hash], index: usize) -> Option<Vec<Hash>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut level: Vec<Hash> = leaves.iter().map(leaf_hash).collect();
+    let mut index = index;
+    let mut path = Vec::new();
+    while level.len() > 1 {
+        let sibling = index ^ 1;
+        if sibling < level.len() {
+            path.push(level[sibling]);
+        }
+        level = parent_level(&level);
+        index /= 2;
+    }
+    Some(path)
+}
+
+/// Whether `proof` shows that `leaf` is part of the tree with the given `root`.
+pub fn verify(root: &Hash, leaf: &Hash, proof: &[Hash]) -> bool {
+    let computed = proof.iter().fold(leaf_hash(leaf), |node, sibling| node_hash(&node, sibling));
+    &computed == root
+}
@@ -0,0 +1,108 @@
+//! Storage migrations for pallet_poe.
+//!
+//! `migrate_to_v2` runs from `on_runtime_upgrade`. The `pre_migrate` and `post_migrate`
+//! checks are test-only: they bracket it in the migration tests on a hand-built `V1` state.
+
+use super::*;
+use frame_support::{
+    storage::migration::StorageIterator,
+    IterableStorageMap, StorageValue,
+};
+
+const MODULE: &[u8] = b"TemplateModule";
+const PROOFS: &[u8] = b"Proofs";
+
+/// The `Proofs` value of a `V1` chain.
+type ClaimV1<T> = (<T as frame_system::Trait>::AccountId, <T as frame_system::Trait>::BlockNumber);
+
+/// Move claims from the `V1` layout, keyed by raw document bytes, to `ClaimInfo` values keyed
+/// by the document's `Blake2_256` digest.
+///
+/// `V1` claims never reserved a deposit, so they are carried over with a zero deposit rather
+/// than charging owners retroactively. The owner index is rebuilt from the migrated claims;
+/// accounts that already held more than `MaxClaimsPerAccount` keep them all but cannot
+/// receive more until they drop below the limit.
+pub fn migrate_to_v2<T: Trait>() -> Weight {
+    if StorageVersion::get() != Releases::V1 {
+        return T::DbWeight::get().reads(1);
+    }
+
+    // The old and new entries share a prefix, so collect the old ones before writing.
+    let old: Vec<(Vec<u8>, ClaimV1<T>)> = StorageIterator::<ClaimV1<T>>::new(MODULE, PROOFS)
+        .drain()
+        .collect();
+
+    let mut count: u32 = 0;
+    for (key, (owner, block_number)) in old {
+        // The key is `blake2_128_concat(document)`: a 16-byte hash followed by the document.
+        let document = match key.get(16..).and_then(|mut raw| Vec::<u8>::decode(&mut raw).ok()) {
+            Some(document) => document,
+            None => continue,
+        };
+        let digest = Proof::Document(HashAlgorithm::Blake2_256, document).digest();
+        Module::<T>::add_to_owner(&owner, &digest);
+        Proofs::<T>::insert(&digest, ClaimInfo {
+            owner,
+            created_at: block_number,
+            updated_at: block_number,
+            deposit: Zero::zero(),
+        });
+        count += 1;
+    }
+
+    ClaimCount::put(count);
+    StorageVersion::put(Releases::V2);
+
+    let count = count as Weight;
+    T::DbWeight::get().reads_writes(1 + 2 * count, 2 + 4 * count)
+}
+
+/// Check the state before `migrate_to_v2`, returning the number of claims to be migrated.
+#[cfg(test)]
+pub fn pre_migrate<T: Trait>() -> Result<u32, &'static str> {
+    if StorageVersion::get() != Releases::V1 {
+        return Err("pallet_poe is not at storage version V1");
+    }
+    if ClaimCountOf::<T>::iter().next().is_some() {
+        return Err("a V1 chain has no owner index");
+    }
+    Ok(StorageIterator::<ClaimV1<T>>::new(MODULE, PROOFS).count() as u32)
+}
+
+/// Check the state after `migrate_to_v2` against the claim count from `pre_migrate`.
+#[cfg(test)]
+pub fn post_migrate<T: Trait>(expected: u32) -> Result<(), &'static str> {
+    if StorageVersion::get() != Releases::V2 {
+        return Err("pallet_poe was not upgraded to storage version V2");
+    }
+
+    let mut migrated = 0u32;
+    for (digest, claim) in Proofs::<T>::iter() {
+        if claim.created_at != claim.updated_at {
+            return Err("migrated claim has diverging creation and update blocks");
+        }
+        if !claim.deposit.is_zero() {
+            return Err("migrated claim has a deposit");
+        }
+        if !ClaimsOf::<T>::contains_key(&claim.owner, &digest) {
+            return Err("migrated claim is missing from its owner's index");
+        }
+        migrated += 1;
+    }
+    if migrated != expected || ClaimCount::get() != expected {
+        return Err("number of claims changed during migration");
+    }
+
+    let mut indexed = 0u32;
+    for (owner, count) in ClaimCountOf::<T>::iter() {
+        if Module::<T>::claims_of(&owner).len() as u32 != count {
+            return Err("owned claim count does not match the owner's index");
+        }
+        indexed += count;
+    }
+    if indexed != expected {
+        return Err("owner index does not match the migrated claims");
+    }
+
+    Ok(())
+}
@@ -43,24 +43,61 @@ impl system::Trait for Test {
 	type AvailableBlockRatio = AvailableBlockRatio;
 	type Version = ();
 	type PalletInfo = ();
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
 }
 
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Trait for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type Event = ();
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
 impl Trait for Test {
 	type Event = ();
-    	type ProofMaxLen = ProofMaxLen;
+	type ProofMaxLen = ProofMaxLen;
+	type Currency = Balances;
+	type ClaimDeposit = ClaimDeposit;
+	type ByteDeposit = ByteDeposit;
+	type MaxExpiriesPerBlock = MaxExpiriesPerBlock;
+	type MetadataMaxLen = MetadataMaxLen;
+	type MaxClaimsPerAccount = MaxClaimsPerAccount;
+	type MaxBatchSize = MaxBatchSize;
+	type MaxAttestations = MaxAttestations;
+	type WeightInfo = ();
 }
 
 parameter_types! {
 	pub const ProofMaxLen: u16 = 512;
+	pub const ClaimDeposit: u64 = 10;
+	pub const ByteDeposit: u64 = 1;
+	pub const MetadataMaxLen: u32 = 64;
+	pub const MaxExpiriesPerBlock: u32 = 2;
+	pub const MaxClaimsPerAccount: u32 = 3;
+	pub const MaxBatchSize: u32 = 3;
+	pub const MaxAttestations: u32 = 2;
 }
 
+pub type System = system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
 pub type PoeModule = Module<Test>;
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 1_000), (2, 1_000), (3, 1_000), (4, 1)],
+	}.assimilate_storage(&mut storage).unwrap();
+	crate::GenesisConfig::default().assimilate_storage::<Test>(&mut storage).unwrap();
+	storage.into()
 }
@@ -1,25 +1,43 @@
 use crate::{Error, mock::*};
-use frame_support::{assert_ok, assert_noop};
+use frame_support::{
+    assert_ok, assert_noop, StorageValue,
+    traits::{Get, OnInitialize, OnRuntimeUpgrade},
+    weights::GetDispatchInfo,
+};
+use sp_runtime::traits::BadOrigin;
 
 use super::*;
 
+fn document(bytes: Vec<u8>) -> Proof {
+    Proof::Document(HashAlgorithm::Blake2_256, bytes)
+}
+
+fn owner_of(digest: &ProofDigest) -> Option<u64> {
+    Proofs::<Test>::get(digest).map(|claim| claim.owner)
+}
+
 #[test]
 fn create_claim_works() {
     new_test_ext().execute_with(|| {
-        let claim: Vec<u8> = vec![0, 2];
-        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
-        assert_eq!(Proofs::<Test>::get(&claim), (1, frame_system::Module::<Test>::block_number()));
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        let now = frame_system::Module::<Test>::block_number();
+        let deposit = PoeModule::deposit_for(&claim.digest(), None);
+        assert_eq!(
+            Proofs::<Test>::get(&claim.digest()),
+            Some(ClaimInfo { owner: 1, created_at: now, updated_at: now, deposit })
+        );
     });
 }
 
 #[test]
 fn create_claim_fails_when_claim_already_exists() {
     new_test_ext().execute_with(|| {
-        let claim: Vec<u8> = vec![0, 2];
-        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
 
         assert_noop!(
-            PoeModule::create_claim(Origin::signed(1), claim.clone()),
+            PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None),
             Error::<Test>::ProofAlreadyClaimed
         );
     });
@@ -28,9 +46,9 @@ fn create_claim_fails_when_claim_already_exists() {
 #[test]
 fn create_claim_fails_when_proof_is_too_long() {
     new_test_ext().execute_with(|| {
-        let claim: Vec<u8> = vec![0; 1024];
+        let claim = document(vec![0; 1024]);
         assert_noop!(
-            PoeModule::create_claim(Origin::signed(1), claim.clone()),
+            PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None),
             Error::<Test>::ProofTooLong
         );
     });
@@ -39,8 +57,8 @@ fn create_claim_fails_when_proof_is_too_long() {
 #[test]
 fn revoke_claim_works() {
     new_test_ext().execute_with(|| {
-        let claim: Vec<u8> = vec![0, 2];
-        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
         assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim.clone()));
     });
 }
@@ -48,7 +66,7 @@ fn revoke_claim_works() {
 #[test]
 fn revoke_claim_fails_when_claim_does_not_exist() {
     new_test_ext().execute_with(|| {
-        let claim: Vec<u8> = vec![0, 2];
+        let claim = document(vec![0, 2]);
         assert_noop!(
             PoeModule::revoke_claim(Origin::signed(1), claim.clone()),
             Error::<Test>::NoSuchProof
@@ -59,8 +77,8 @@ fn revoke_claim_fails_when_claim_does_not_exist() {
 #[test]
 fn revoke_claim_fails_when_request_by_not_owner() {
     new_test_ext().execute_with(|| {
-        let claim: Vec<u8> = vec![0, 2];
-        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
         assert_noop!(
             PoeModule::revoke_claim(Origin::signed(2), claim.clone()),
             Error::<Test>::NotProofOwner
@@ -71,32 +89,873 @@ fn revoke_claim_fails_when_request_by_not_owner() {
 #[test]
 fn transfer_claim_works() {
     new_test_ext().execute_with(|| {
-        let claim: Vec<u8> = vec![0, 2];
-        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
-        assert_ok!(PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 2));
-        assert_eq!(Proofs::<Test>::get(&claim), (2, frame_system::Module::<Test>::block_number()));
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        System::set_block_number(5);
+        assert_ok!(PoeModule::transfer_claim(Origin::root(), claim.clone(), 2));
+        let info = Proofs::<Test>::get(&claim.digest()).unwrap();
+        assert_eq!(info.owner, 2);
+        assert_eq!(info.created_at, 0);
+        assert_eq!(info.updated_at, 5);
     });
 }
 
 #[test]
 fn transfer_claim_fails_when_claim_does_not_exist() {
     new_test_ext().execute_with(|| {
-        let claim: Vec<u8> = vec![0, 2];
+        let claim = document(vec![0, 2]);
         assert_noop!(
-            PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 2),
+            PoeModule::transfer_claim(Origin::root(), claim.clone(), 2),
             Error::<Test>::NoSuchProof
         );
     });
 }
 
 #[test]
-fn transfer_claim_fails_when_request_by_not_owner() {
+fn transfer_claim_fails_when_not_root() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_noop!(
+            PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 3),
+            BadOrigin
+        );
+    });
+}
+
+#[test]
+fn document_and_digest_resolve_to_the_same_claim() {
+    new_test_ext().execute_with(|| {
+        let bytes: Vec<u8> = vec![0, 2];
+        let digest = Proof::Digest(ProofDigest {
+            algorithm: HashAlgorithm::Sha2_256,
+            hash: sp_io::hashing::sha2_256(&bytes),
+        });
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), digest.clone(), None, None));
+
+        let claim = Proof::Document(HashAlgorithm::Sha2_256, bytes);
+        assert_eq!(PoeModule::claim_of(&claim), PoeModule::claim_of(&digest));
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(2), claim.clone(), None, None),
+            Error::<Test>::ProofAlreadyClaimed
+        );
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim));
+        assert_eq!(PoeModule::claim_of(&digest), None);
+    });
+}
+
+#[test]
+fn digests_of_different_algorithms_are_distinct_claims() {
+    new_test_ext().execute_with(|| {
+        let bytes: Vec<u8> = vec![0, 2];
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), document(bytes.clone()), None, None));
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(2),
+            Proof::Document(HashAlgorithm::Keccak256, bytes),
+            None,
+            None
+        ));
+    });
+}
+
+#[test]
+fn create_claim_reserves_deposit() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        let deposit = PoeModule::deposit_for(&claim.digest(), None);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_eq!(Balances::reserved_balance(1), deposit);
+        assert_eq!(PoeModule::proofs(&claim.digest()).unwrap().deposit, deposit);
+    });
+}
+
+#[test]
+fn create_claim_fails_without_funds_for_deposit() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(4), document(vec![0, 2]), None, None),
+            pallet_balances::Error::<Test, pallet_balances::DefaultInstance>::InsufficientBalance
+        );
+    });
+}
+
+#[test]
+fn revoke_claim_unreserves_deposit() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim.clone()));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 1_000);
+        assert!(!Proofs::<Test>::contains_key(&claim.digest()));
+    });
+}
+
+#[test]
+fn transfer_claim_moves_deposit_to_new_owner() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        let deposit = PoeModule::deposit_for(&claim.digest(), None);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_ok!(PoeModule::offer_claim(Origin::signed(1), claim.clone(), 2));
+        assert_ok!(PoeModule::accept_claim(Origin::signed(2), claim.clone()));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 1_000 - deposit);
+        assert_eq!(Balances::reserved_balance(2), deposit);
+    });
+}
+
+#[test]
+fn transfer_claim_leaves_nothing_reserved_on_old_owner() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        let deposit = PoeModule::deposit_for(&claim.digest(), None);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        // Part of the deposit was slashed elsewhere, so only the rest can be moved.
+        let _ = Balances::slash_reserved(&1, 5);
+
+        assert_ok!(PoeModule::transfer_claim(Origin::root(), claim.clone(), 2));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(2), deposit - 5);
+        assert_eq!(PoeModule::proofs(&claim.digest()).unwrap().deposit, deposit - 5);
+    });
+}
+
+fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        System::set_block_number(System::block_number() + 1);
+        PoeModule::on_initialize(System::block_number());
+    }
+}
+
+#[test]
+fn create_claim_fails_when_expiry_is_in_the_past() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(1), document(vec![0, 2]), Some(5), None),
+            Error::<Test>::InvalidExpiry
+        );
+    });
+}
+
+#[test]
+fn claim_expires_at_expiry_block() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), Some(3), None));
+        run_to_block(2);
+        assert!(Proofs::<Test>::contains_key(&claim.digest()));
+
+        run_to_block(3);
+        assert!(!Proofs::<Test>::contains_key(&claim.digest()));
+        assert_eq!(PoeModule::claim_expiry(&claim.digest()), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+    });
+}
+
+#[test]
+fn renew_claim_extends_expiry() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), Some(3), None));
+        assert_ok!(PoeModule::renew_claim(Origin::signed(1), claim.clone(), Some(6)));
+        assert!(PoeModule::expiring_claims(3).is_empty());
+
+        run_to_block(5);
+        assert!(Proofs::<Test>::contains_key(&claim.digest()));
+        run_to_block(6);
+        assert!(!Proofs::<Test>::contains_key(&claim.digest()));
+    });
+}
+
+#[test]
+fn renew_claim_to_none_makes_claim_permanent() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), Some(3), None));
+        assert_ok!(PoeModule::renew_claim(Origin::signed(1), claim.clone(), None));
+        run_to_block(10);
+        assert!(Proofs::<Test>::contains_key(&claim.digest()));
+    });
+}
+
+#[test]
+fn renew_claim_fails_when_request_by_not_owner() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), Some(3), None));
+        assert_noop!(
+            PoeModule::renew_claim(Origin::signed(2), claim.clone(), Some(6)),
+            Error::<Test>::NotProofOwner
+        );
+    });
+}
+
+#[test]
+fn expiries_per_block_are_capped() {
+    new_test_ext().execute_with(|| {
+        let claims: Vec<Proof> = (0..3u8).map(|i| document(vec![i])).collect();
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claims[0].clone(), Some(2), None));
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claims[1].clone(), Some(2), None));
+
+        // `MaxExpiriesPerBlock` is 2 in the mock.
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(1), claims[2].clone(), Some(2), None),
+            Error::<Test>::TooManyExpiries
+        );
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claims[2].clone(), None, None));
+        assert_noop!(
+            PoeModule::renew_claim(Origin::signed(1), claims[2].clone(), Some(2)),
+            Error::<Test>::TooManyExpiries
+        );
+        // Renewing to the block a claim already expires at does not count it twice.
+        assert_ok!(PoeModule::renew_claim(Origin::signed(1), claims[0].clone(), Some(2)));
+        assert_eq!(PoeModule::expiring_claims(2).len(), 2);
+
+        run_to_block(2);
+        assert!(!Proofs::<Test>::contains_key(&claims[0].digest()));
+        assert!(!Proofs::<Test>::contains_key(&claims[1].digest()));
+        assert!(Proofs::<Test>::contains_key(&claims[2].digest()));
+    });
+}
+
+#[test]
+fn offer_and_accept_claim_works() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_ok!(PoeModule::offer_claim(Origin::signed(1), claim.clone(), 2));
+        assert_eq!(PoeModule::pending_transfer(&claim.digest()), Some(2));
+        // Ownership does not move until the recipient accepts.
+        assert_eq!(owner_of(&claim.digest()), Some(1));
+
+        assert_ok!(PoeModule::accept_claim(Origin::signed(2), claim.clone()));
+        assert_eq!(owner_of(&claim.digest()), Some(2));
+        assert_eq!(PoeModule::pending_transfer(&claim.digest()), None);
+    });
+}
+
+#[test]
+fn offer_claim_fails_when_request_by_not_owner() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_noop!(
+            PoeModule::offer_claim(Origin::signed(2), claim.clone(), 3),
+            Error::<Test>::NotProofOwner
+        );
+    });
+}
+
+#[test]
+fn accept_claim_fails_when_not_offered() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_noop!(
+            PoeModule::accept_claim(Origin::signed(2), claim.clone()),
+            Error::<Test>::NoPendingOffer
+        );
+    });
+}
+
+#[test]
+fn accept_claim_fails_when_request_by_other_account() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_ok!(PoeModule::offer_claim(Origin::signed(1), claim.clone(), 2));
+        assert_noop!(
+            PoeModule::accept_claim(Origin::signed(3), claim.clone()),
+            Error::<Test>::NotOfferRecipient
+        );
+    });
+}
+
+#[test]
+fn cancel_offer_works() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_ok!(PoeModule::offer_claim(Origin::signed(1), claim.clone(), 2));
+        assert_noop!(
+            PoeModule::cancel_offer(Origin::signed(2), claim.clone()),
+            Error::<Test>::NotProofOwner
+        );
+        assert_ok!(PoeModule::cancel_offer(Origin::signed(1), claim.clone()));
+        assert_noop!(
+            PoeModule::accept_claim(Origin::signed(2), claim.clone()),
+            Error::<Test>::NoPendingOffer
+        );
+    });
+}
+
+#[test]
+fn revoke_claim_drops_pending_offer() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_ok!(PoeModule::offer_claim(Origin::signed(1), claim.clone(), 2));
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim.clone()));
+        assert_eq!(PoeModule::pending_transfer(&claim.digest()), None);
+    });
+}
+
+fn metadata() -> ClaimMetadata {
+    ClaimMetadata {
+        mime_type: b"application/pdf".to_vec(),
+        file_size: Some(1024),
+        title: b"Lease agreement".to_vec(),
+        uri: b"ipfs://lease".to_vec(),
+    }
+}
+
+#[test]
+fn create_claim_with_metadata_works() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, Some(metadata())));
+        assert_eq!(PoeModule::metadata_of(&claim.digest()), Some(metadata()));
+        assert_eq!(
+            Balances::reserved_balance(1),
+            PoeModule::deposit_for(&claim.digest(), Some(&metadata()))
+        );
+    });
+}
+
+#[test]
+fn create_claim_fails_when_metadata_is_too_long() {
+    new_test_ext().execute_with(|| {
+        let metadata = ClaimMetadata { title: vec![0; 65], ..Default::default() };
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(1), document(vec![0, 2]), None, Some(metadata)),
+            Error::<Test>::MetadataTooLong
+        );
+    });
+}
+
+#[test]
+fn set_metadata_adjusts_deposit() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+
+        assert_ok!(PoeModule::set_metadata(Origin::signed(1), claim.clone(), Some(metadata())));
+        assert_eq!(PoeModule::metadata_of(&claim.digest()), Some(metadata()));
+        assert_eq!(
+            Balances::reserved_balance(1),
+            PoeModule::deposit_for(&claim.digest(), Some(&metadata()))
+        );
+
+        assert_ok!(PoeModule::set_metadata(Origin::signed(1), claim.clone(), None));
+        assert_eq!(PoeModule::metadata_of(&claim.digest()), None);
+        assert_eq!(Balances::reserved_balance(1), PoeModule::deposit_for(&claim.digest(), None));
+    });
+}
+
+#[test]
+fn set_metadata_fails_when_request_by_not_owner() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_noop!(
+            PoeModule::set_metadata(Origin::signed(2), claim.clone(), Some(metadata())),
+            Error::<Test>::NotProofOwner
+        );
+    });
+}
+
+#[test]
+fn revoke_claim_removes_metadata() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, Some(metadata())));
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim.clone()));
+        assert_eq!(PoeModule::metadata_of(&claim.digest()), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+    });
+}
+
+#[test]
+fn claims_of_tracks_ownership() {
+    new_test_ext().execute_with(|| {
+        let first = document(vec![0, 1]);
+        let second = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), first.clone(), None, None));
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), second.clone(), None, None));
+        let mut owned = PoeModule::claims_of(&1);
+        owned.sort_by_key(|digest| digest.encode());
+        let mut expected = vec![first.digest(), second.digest()];
+        expected.sort_by_key(|digest| digest.encode());
+        assert_eq!(owned, expected);
+        assert_eq!(PoeModule::claim_count_of(1), 2);
+
+        assert_ok!(PoeModule::transfer_claim(Origin::root(), first.clone(), 2));
+        assert_eq!(PoeModule::claims_of(&1), vec![second.digest()]);
+        assert_eq!(PoeModule::claims_of(&2), vec![first.digest()]);
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), second.clone()));
+        assert!(PoeModule::claims_of(&1).is_empty());
+        assert!(!ClaimCountOf::<Test>::contains_key(1));
+    });
+}
+
+#[test]
+fn create_claim_fails_when_account_owns_too_many_claims() {
+    new_test_ext().execute_with(|| {
+        for i in 0..3u8 {
+            assert_ok!(PoeModule::create_claim(Origin::signed(1), document(vec![i]), None, None));
+        }
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(1), document(vec![3]), None, None),
+            Error::<Test>::TooManyClaims
+        );
+    });
+}
+
+#[test]
+fn accept_claim_fails_when_recipient_owns_too_many_claims() {
     new_test_ext().execute_with(|| {
-        let claim: Vec<u8> = vec![0, 2];
-        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+        for i in 0..3u8 {
+            assert_ok!(PoeModule::create_claim(Origin::signed(2), document(vec![i]), None, None));
+        }
+        let claim = document(vec![3]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_ok!(PoeModule::offer_claim(Origin::signed(1), claim.clone(), 2));
         assert_noop!(
-            PoeModule::transfer_claim(Origin::signed(2), claim.clone(), 3),
+            PoeModule::accept_claim(Origin::signed(2), claim.clone()),
+            Error::<Test>::TooManyClaims
+        );
+    });
+}
+
+#[test]
+fn expired_claims_leave_the_owner_index() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), Some(2), None));
+        run_to_block(2);
+        assert!(PoeModule::claims_of(&1).is_empty());
+    });
+}
+
+#[test]
+fn create_claims_works() {
+    new_test_ext().execute_with(|| {
+        let claims: Vec<Proof> = (0..3u8).map(|i| document(vec![i])).collect();
+        assert_ok!(PoeModule::create_claims(Origin::signed(1), claims.clone(), false));
+        assert!(claims.iter().all(|c| owner_of(&c.digest()) == Some(1)));
+    });
+}
+
+#[test]
+fn create_claims_is_atomic_by_default() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), document(vec![1]), None, None));
+        let claims: Vec<Proof> = (0..3u8).map(|i| document(vec![i])).collect();
+        assert_noop!(
+            PoeModule::create_claims(Origin::signed(1), claims.clone(), false),
+            Error::<Test>::ProofAlreadyClaimed
+        );
+        assert!(!Proofs::<Test>::contains_key(&claims[0].digest()));
+        assert_eq!(Balances::reserved_balance(1), 0);
+    });
+}
+
+#[test]
+fn create_claims_best_effort_skips_failures() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), document(vec![1]), None, None));
+        let claims: Vec<Proof> = (0..3u8).map(|i| document(vec![i])).collect();
+        assert_ok!(PoeModule::create_claims(Origin::signed(1), claims.clone(), true));
+        assert_eq!(owner_of(&claims[0].digest()), Some(1));
+        assert_eq!(owner_of(&claims[1].digest()), Some(2));
+        assert_eq!(owner_of(&claims[2].digest()), Some(1));
+        assert_eq!(PoeModule::claims_of(&1).len(), 2);
+    });
+}
+
+#[test]
+fn create_claims_fails_when_batch_is_too_large() {
+    new_test_ext().execute_with(|| {
+        let claims: Vec<Proof> = (0..4u8).map(|i| document(vec![i])).collect();
+        assert_noop!(
+            PoeModule::create_claims(Origin::signed(1), claims, true),
+            Error::<Test>::BatchTooLarge
+        );
+    });
+}
+
+#[test]
+fn revoke_claims_works() {
+    new_test_ext().execute_with(|| {
+        let claims: Vec<Proof> = (0..3u8).map(|i| document(vec![i])).collect();
+        assert_ok!(PoeModule::create_claims(Origin::signed(1), claims.clone(), false));
+        assert_ok!(PoeModule::revoke_claims(Origin::signed(1), claims.clone(), false));
+        assert!(claims.iter().all(|c| !Proofs::<Test>::contains_key(&c.digest())));
+        assert_eq!(Balances::reserved_balance(1), 0);
+    });
+}
+
+#[test]
+fn revoke_claims_is_atomic_by_default() {
+    new_test_ext().execute_with(|| {
+        let claims: Vec<Proof> = (0..2u8).map(|i| document(vec![i])).collect();
+        assert_ok!(PoeModule::create_claims(Origin::signed(1), claims.clone(), false));
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), document(vec![2]), None, None));
+
+        let mut to_revoke = claims.clone();
+        to_revoke.push(document(vec![2]));
+        assert_noop!(
+            PoeModule::revoke_claims(Origin::signed(1), to_revoke.clone(), false),
+            Error::<Test>::NotProofOwner
+        );
+
+        assert_ok!(PoeModule::revoke_claims(Origin::signed(1), to_revoke, true));
+        assert!(claims.iter().all(|c| !Proofs::<Test>::contains_key(&c.digest())));
+        assert_eq!(owner_of(&document(vec![2]).digest()), Some(2));
+    });
+}
+
+#[test]
+fn batch_weight_grows_with_count_and_length() {
+    let weight = |proofs: Vec<Proof>| Call::<Test>::create_claims(proofs, false).get_dispatch_info().weight;
+    let short = weight(vec![document(vec![0; 4])]);
+    let long = weight(vec![document(vec![0; 400])]);
+    let many = weight(vec![document(vec![0; 4]), document(vec![1; 4])]);
+    assert!(long > short);
+    assert!(many > short);
+}
+
+#[test]
+fn create_claim_weight_grows_with_metadata() {
+    let weight = |metadata| Call::<Test>::create_claim(document(vec![0; 4]), None, metadata).get_dispatch_info().weight;
+    assert!(weight(Some(metadata())) > weight(None));
+}
+
+fn leaves(count: u8) -> Vec<merkle::Hash> {
+    (0..count).map(|i| sp_io::hashing::blake2_256(&[i])).collect()
+}
+
+#[test]
+fn merkle_proofs_verify_for_every_leaf() {
+    for count in 1..=7u8 {
+        let leaves = leaves(count);
+        let root = merkle::root(&leaves).unwrap();
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = merkle::proof(&leaves, index).unwrap();
+            assert!(merkle::verify(&root, leaf, &proof));
+        }
+    }
+}
+
+#[test]
+fn merkle_proof_rejects_wrong_leaf() {
+    let leaves = leaves(5);
+    let root = merkle::root(&leaves).unwrap();
+    let proof = merkle::proof(&leaves, 2).unwrap();
+    assert!(!merkle::verify(&root, &leaves[3], &proof));
+    assert!(merkle::root(&[]).is_none());
+    assert!(merkle::proof(&leaves, 5).is_none());
+}
+
+#[test]
+fn verify_inclusion_works_for_anchored_root() {
+    new_test_ext().execute_with(|| {
+        let leaves = leaves(5);
+        let root = merkle::root(&leaves).unwrap();
+        assert_ok!(PoeModule::anchor_root(Origin::signed(1), root, None, None));
+        assert_eq!(PoeModule::claim_kind(&PoeModule::root_digest(root)), ClaimKind::MerkleRoot);
+
+        let proof = merkle::proof(&leaves, 3).unwrap();
+        assert!(PoeModule::verify_inclusion(root, leaves[3], proof.clone()));
+        assert!(!PoeModule::verify_inclusion(root, leaves[1], proof));
+    });
+}
+
+#[test]
+fn verify_inclusion_fails_for_unanchored_root() {
+    new_test_ext().execute_with(|| {
+        let leaves = leaves(4);
+        let root = merkle::root(&leaves).unwrap();
+        let proof = merkle::proof(&leaves, 0).unwrap();
+        assert!(!PoeModule::verify_inclusion(root, leaves[0], proof.clone()));
+
+        // A plain document claim on the same digest is not a root claim.
+        let claim = Proof::Digest(PoeModule::root_digest(root));
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert!(!PoeModule::verify_inclusion(root, leaves[0], proof.clone()));
+
+        // Nor is a revoked root.
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim.clone()));
+        assert_ok!(PoeModule::anchor_root(Origin::signed(1), root, None, None));
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim));
+        assert!(!PoeModule::verify_inclusion(root, leaves[0], proof));
+    });
+}
+
+#[test]
+fn approve_and_revoke_operator_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::approve_operator(Origin::signed(1), 3));
+        assert!(PoeModule::is_operator(1, 3));
+        assert_ok!(PoeModule::revoke_operator(Origin::signed(1), 3));
+        assert!(!PoeModule::is_operator(1, 3));
+        assert_noop!(
+            PoeModule::revoke_operator(Origin::signed(1), 3),
+            Error::<Test>::NotOperator
+        );
+    });
+}
+
+#[test]
+fn operator_can_revoke_claim() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_noop!(
+            PoeModule::revoke_claim(Origin::signed(3), claim.clone()),
             Error::<Test>::NotProofOwner
         );
+
+        assert_ok!(PoeModule::approve_operator(Origin::signed(1), 3));
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(3), claim.clone()));
+        assert!(!Proofs::<Test>::contains_key(&claim.digest()));
+        // The deposit goes back to the owner, not the operator.
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 1_000);
+    });
+}
+
+#[test]
+fn operator_can_offer_and_cancel_claim() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_ok!(PoeModule::approve_operator(Origin::signed(1), 3));
+
+        assert_ok!(PoeModule::offer_claim(Origin::signed(3), claim.clone(), 2));
+        assert_ok!(PoeModule::cancel_offer(Origin::signed(3), claim.clone()));
+        assert_eq!(PoeModule::pending_transfer(&claim.digest()), None);
+
+        assert_ok!(PoeModule::offer_claim(Origin::signed(3), claim.clone(), 2));
+        assert_ok!(PoeModule::accept_claim(Origin::signed(2), claim.clone()));
+        assert_eq!(owner_of(&claim.digest()), Some(2));
+    });
+}
+
+#[test]
+fn operator_approval_is_per_owner() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), claim.clone(), None, None));
+        assert_ok!(PoeModule::approve_operator(Origin::signed(1), 3));
+        assert_noop!(
+            PoeModule::offer_claim(Origin::signed(3), claim.clone(), 1),
+            Error::<Test>::NotProofOwner
+        );
+    });
+}
+
+/// Write a claim the way a `V1` chain stored it: keyed by the raw document bytes.
+fn put_v1_claim(document: &[u8], owner: u64, block_number: u64) {
+    use codec::Encode;
+    use frame_support::{Blake2_128Concat, StorageHasher, storage::migration::put_storage_value};
+
+    let key = Blake2_128Concat::hash(&document.to_vec().encode());
+    put_storage_value(b"TemplateModule", b"Proofs", &key, (owner, block_number));
+}
+
+#[test]
+fn genesis_is_at_latest_storage_version() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(StorageVersion::get(), Releases::V2);
+        assert_eq!(migrations::migrate_to_v2::<Test>(), <Test as frame_system::Trait>::DbWeight::get().reads(1));
+    });
+}
+
+#[test]
+fn migrate_to_v2_works() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::put(Releases::V1);
+        put_v1_claim(&[0, 2], 1, 3);
+        put_v1_claim(&[1], 1, 4);
+        put_v1_claim(&[7, 7, 7], 2, 5);
+
+        let expected = migrations::pre_migrate::<Test>().unwrap();
+        assert_eq!(expected, 3);
+        PoeModule::on_runtime_upgrade();
+        assert_ok!(migrations::post_migrate::<Test>(expected));
+        assert_eq!(PoeModule::claim_count(), 3);
+
+        assert_eq!(
+            PoeModule::claim_of(&document(vec![0, 2])),
+            Some(ClaimInfo { owner: 1, created_at: 3, updated_at: 3, deposit: 0 })
+        );
+        assert_eq!(PoeModule::claims_of(&1).len(), 2);
+        assert_eq!(PoeModule::claims_of(&2), vec![document(vec![7, 7, 7]).digest()]);
+
+        // Migrated claims behave like any other claim.
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), document(vec![1])));
+        assert_eq!(Balances::free_balance(1), 1_000);
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(3), document(vec![7, 7, 7]), None, None),
+            Error::<Test>::ProofAlreadyClaimed
+        );
+    });
+}
+
+#[test]
+fn migrate_to_v2_runs_only_once() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::put(Releases::V1);
+        put_v1_claim(&[0, 2], 1, 3);
+        PoeModule::on_runtime_upgrade();
+        assert_eq!(StorageVersion::get(), Releases::V2);
+
+        let before = PoeModule::claim_of(&document(vec![0, 2]));
+        PoeModule::on_runtime_upgrade();
+        assert_eq!(PoeModule::claim_of(&document(vec![0, 2])), before);
+        assert_eq!(PoeModule::claims_of(&1).len(), 1);
+        assert!(migrations::pre_migrate::<Test>().is_err());
+    });
+}
+
+#[test]
+fn attest_works() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_ok!(PoeModule::attest(Origin::signed(2), claim.clone()));
+        assert_ok!(PoeModule::attest(Origin::signed(3), claim.clone()));
+        assert_eq!(PoeModule::attestations_of(&claim.digest()), vec![2, 3]);
+    });
+}
+
+#[test]
+fn attest_fails_for_invalid_attesters() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_noop!(
+            PoeModule::attest(Origin::signed(2), claim.clone()),
+            Error::<Test>::NoSuchProof
+        );
+
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_noop!(
+            PoeModule::attest(Origin::signed(1), claim.clone()),
+            Error::<Test>::CannotAttestOwnClaim
+        );
+
+        assert_ok!(PoeModule::attest(Origin::signed(2), claim.clone()));
+        assert_noop!(
+            PoeModule::attest(Origin::signed(2), claim.clone()),
+            Error::<Test>::AlreadyAttested
+        );
+
+        assert_ok!(PoeModule::attest(Origin::signed(3), claim.clone()));
+        assert_noop!(
+            PoeModule::attest(Origin::signed(4), claim.clone()),
+            Error::<Test>::TooManyAttestations
+        );
+    });
+}
+
+#[test]
+fn withdraw_attestation_works() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_noop!(
+            PoeModule::withdraw_attestation(Origin::signed(2), claim.clone()),
+            Error::<Test>::NotAttested
+        );
+
+        assert_ok!(PoeModule::attest(Origin::signed(2), claim.clone()));
+        assert_ok!(PoeModule::attest(Origin::signed(3), claim.clone()));
+        assert_ok!(PoeModule::withdraw_attestation(Origin::signed(2), claim.clone()));
+        assert_eq!(PoeModule::attestations_of(&claim.digest()), vec![3]);
+        assert_ok!(PoeModule::withdraw_attestation(Origin::signed(3), claim.clone()));
+        assert!(!Attestations::<Test>::contains_key(&claim.digest()));
+    });
+}
+
+#[test]
+fn attestations_survive_transfer_and_are_removed_with_the_claim() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_ok!(PoeModule::attest(Origin::signed(3), claim.clone()));
+
+        assert_ok!(PoeModule::transfer_claim(Origin::root(), claim.clone(), 2));
+        assert_eq!(PoeModule::attestations_of(&claim.digest()), vec![3]);
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(2), claim.clone()));
+        assert!(!Attestations::<Test>::contains_key(&claim.digest()));
+    });
+}
+
+#[test]
+fn attestations_reserve_a_deposit() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        // An account id is 8 bytes in the mock and `ByteDeposit` is 1.
+        assert_eq!(PoeModule::attestation_deposit(&2), 8);
+
+        assert_ok!(PoeModule::attest(Origin::signed(2), claim.clone()));
+        assert_ok!(PoeModule::attest(Origin::signed(3), claim.clone()));
+        assert_eq!(Balances::reserved_balance(2), 8);
+        assert_eq!(Balances::reserved_balance(3), 8);
+
+        assert_ok!(PoeModule::withdraw_attestation(Origin::signed(2), claim.clone()));
+        assert_eq!(Balances::reserved_balance(2), 0);
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim.clone()));
+        assert_eq!(Balances::reserved_balance(3), 0);
+        assert_eq!(Balances::free_balance(3), 1_000);
+    });
+}
+
+#[test]
+fn attest_fails_without_funds_for_the_deposit() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), None, None));
+        assert_noop!(
+            PoeModule::attest(Origin::signed(4), claim.clone()),
+            pallet_balances::Error::<Test, pallet_balances::DefaultInstance>::InsufficientBalance
+        );
+    });
+}
+
+#[test]
+fn expired_claims_return_attestation_deposits() {
+    new_test_ext().execute_with(|| {
+        let claim = document(vec![0, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone(), Some(2), None));
+        assert_ok!(PoeModule::attest(Origin::signed(2), claim.clone()));
+
+        run_to_block(2);
+        assert!(!Attestations::<Test>::contains_key(&claim.digest()));
+        assert_eq!(Balances::reserved_balance(2), 0);
+    });
+}
+
+#[test]
+fn claim_count_tracks_created_and_removed_claims() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), document(vec![0]), None, None));
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), document(vec![1]), Some(2), None));
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), document(vec![2]), None, None));
+        assert_eq!(PoeModule::claim_count(), 3);
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), document(vec![0])));
+        assert_ok!(PoeModule::transfer_claim(Origin::root(), document(vec![2]), 3));
+        assert_eq!(PoeModule::claim_count(), 2);
+
+        run_to_block(2);
+        assert_eq!(PoeModule::claim_count(), 1);
     });
 }
@@ -0,0 +1,108 @@
+//! Weights for pallet_poe.
+//!
+//! No benchmark results have been recorded yet. The only implementation is the `()`
+//! placeholder: hand-counted database reads and writes priced with `RocksDbWeight`, plus the
+//! flat per-call and per-byte execution weights the pallet used before it was benchmarked. They
+//! are not measurements. Run the benchmarks in `benchmarking.rs` on reference hardware with:
+//!
+//! ```text
+//! ./target/release/node-template benchmark --chain dev --execution wasm --wasm-execution compiled \
+//!     --pallet pallet_poe --extrinsic '*' --steps 50 --repeat 20
+//! ```
+//!
+//! and add the generated `SubstrateWeight` here before a runtime relies on these weights.
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+
+/// Weight functions needed for pallet_poe.
+pub trait WeightInfo {
+    fn create_claim(l: u32, m: u32) -> Weight;
+    fn revoke_claim(l: u32) -> Weight;
+    fn anchor_root(m: u32) -> Weight;
+    fn create_claims(n: u32, l: u32) -> Weight;
+    fn revoke_claims(n: u32, l: u32) -> Weight;
+    fn offer_claim(l: u32) -> Weight;
+    fn accept_claim(l: u32) -> Weight;
+    fn cancel_offer(l: u32) -> Weight;
+    fn transfer_claim(l: u32) -> Weight;
+    fn renew_claim(l: u32) -> Weight;
+    fn set_metadata(l: u32, m: u32) -> Weight;
+    fn approve_operator() -> Weight;
+    fn revoke_operator() -> Weight;
+    fn attest(l: u32) -> Weight;
+    fn withdraw_attestation(l: u32) -> Weight;
+}
+
+/// The execution weight of a call, before benchmarking.
+const CALL: Weight = 10_000;
+/// The execution weight of each byte a call submits, before benchmarking.
+const BYTE: Weight = 10;
+
+fn per_byte(len: u32) -> Weight {
+    (len as Weight).saturating_mul(BYTE)
+}
+
+fn db(reads: Weight, writes: Weight) -> Weight {
+    RocksDbWeight::get().reads_writes(reads, writes)
+}
+
+fn call(l: u32, reads: Weight, writes: Weight) -> Weight {
+    CALL.saturating_add(per_byte(l)).saturating_add(db(reads, writes))
+}
+
+fn batch(n: u32, l: u32, reads: Weight, writes: Weight) -> Weight {
+    let n = n as Weight;
+    CALL.saturating_add(n.saturating_mul(CALL))
+        .saturating_add(per_byte(l))
+        .saturating_add(db(n.saturating_mul(reads), n.saturating_mul(writes)))
+}
+
+// Placeholder weights, until benchmark results are recorded. The reads and writes assume the
+// worst case of each call, e.g. an expiry and metadata on a new claim.
+impl WeightInfo for () {
+    fn create_claim(l: u32, m: u32) -> Weight {
+        call(l, 6, 8).saturating_add(per_byte(m))
+    }
+    fn revoke_claim(l: u32) -> Weight {
+        call(l, 9, 10)
+    }
+    fn anchor_root(m: u32) -> Weight {
+        call(0, 6, 9).saturating_add(per_byte(m))
+    }
+    fn create_claims(n: u32, l: u32) -> Weight {
+        batch(n, l, 6, 8)
+    }
+    fn revoke_claims(n: u32, l: u32) -> Weight {
+        batch(n, l, 9, 10)
+    }
+    fn offer_claim(l: u32) -> Weight {
+        call(l, 2, 1)
+    }
+    fn accept_claim(l: u32) -> Weight {
+        call(l, 6, 8)
+    }
+    fn cancel_offer(l: u32) -> Weight {
+        call(l, 3, 1)
+    }
+    fn transfer_claim(l: u32) -> Weight {
+        call(l, 6, 8)
+    }
+    fn renew_claim(l: u32) -> Weight {
+        call(l, 4, 4)
+    }
+    fn set_metadata(l: u32, m: u32) -> Weight {
+        call(l, 3, 3).saturating_add(per_byte(m))
+    }
+    fn approve_operator() -> Weight {
+        call(0, 0, 1)
+    }
+    fn revoke_operator() -> Weight {
+        call(0, 1, 1)
+    }
+    fn attest(l: u32) -> Weight {
+        call(l, 3, 2)
+    }
+    fn withdraw_attestation(l: u32) -> Weight {
+        call(l, 2, 2)
+    }
+}
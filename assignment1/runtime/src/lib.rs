@@ -95,7 +95,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("node-template"),
 	impl_name: create_runtime_str!("node-template"),
 	authoring_version: 1,
-	spec_version: 1,
+	spec_version: 2,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -270,10 +270,27 @@ impl pallet_template::Trait for Runtime {
 impl pallet_poe::Trait for Runtime {
 	type Event = Event;
 	type ProofMaxLen = ProofMaxLen;
+	type Currency = Balances;
+	type ClaimDeposit = ClaimDeposit;
+	type ByteDeposit = ByteDeposit;
+	type MaxExpiriesPerBlock = MaxExpiriesPerBlock;
+	type MetadataMaxLen = MetadataMaxLen;
+	type MaxClaimsPerAccount = MaxClaimsPerAccount;
+	type MaxBatchSize = MaxBatchSize;
+	type MaxAttestations = MaxAttestations;
+	// Hand-counted placeholders until benchmark results are recorded, see `pallet_poe::weights`.
+	type WeightInfo = ();
 }
 
 parameter_types! {
 	pub const ProofMaxLen: u16 = 512;
+	pub const ClaimDeposit: Balance = 1_000;
+	pub const ByteDeposit: Balance = 10;
+	pub const MetadataMaxLen: u32 = 256;
+	pub const MaxExpiriesPerBlock: u32 = 100;
+	pub const MaxClaimsPerAccount: u32 = 1_000;
+	pub const MaxBatchSize: u32 = 100;
+	pub const MaxAttestations: u32 = 16;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -293,7 +310,7 @@ construct_runtime!(
 		Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
 		// Include the custom logic from the template pallet in the runtime.
 		TemplateModule: pallet_template::{Module, Call, Storage, Event<T>},
-		PoeModule: pallet_poe::{Module, Call, Storage, Event<T>},
+		PoeModule: pallet_poe::{Module, Call, Config, Storage, Event<T>},
 	}
 );
 
@@ -454,6 +471,30 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_poe_runtime_api::PoeApi<Block, AccountId, BlockNumber, Balance> for Runtime {
+		fn claim_of(
+			proof: pallet_poe::Proof,
+		) -> Option<pallet_poe::ClaimInfo<AccountId, BlockNumber, Balance>> {
+			PoeModule::claim_of(&proof)
+		}
+
+		fn claims_of(owner: AccountId) -> Vec<pallet_poe::ProofDigest> {
+			PoeModule::claims_of(&owner)
+		}
+
+		fn claim_count() -> u32 {
+			PoeModule::claim_count()
+		}
+
+		fn verify_inclusion(root: [u8; 32], leaf: [u8; 32], proof: Vec<[u8; 32]>) -> bool {
+			PoeModule::verify_inclusion(root, leaf, proof)
+		}
+
+		fn attestations_of(claim: pallet_poe::ProofDigest) -> Vec<AccountId> {
+			PoeModule::attestations_of(claim)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(
@@ -483,6 +524,7 @@ impl_runtime_apis! {
 			add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
 			add_benchmark!(params, batches, pallet_balances, Balances);
 			add_benchmark!(params, batches, pallet_timestamp, Timestamp);
+			add_benchmark!(params, batches, pallet_poe, PoeModule);
 
 			if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
 			Ok(batches)
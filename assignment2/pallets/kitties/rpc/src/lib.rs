@@ -0,0 +1,123 @@
+//! RPC interface for the kitties pallet.
+//!
+//! A node whose runtime implements `KittiesRuntimeApi` adds these methods in its `rpc.rs`
+//! `create_full`, next to the other extensions:
+//!
+//! ```ignore
+//! C::Api: pallet_kitties_rpc::KittiesRuntimeApi<Block, KittyIndex>,
+//! ...
+//! io.extend_with(pallet_kitties_rpc::KittiesApi::to_delegate(pallet_kitties_rpc::Kitties::new(client.clone())));
+//! ```
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_kitties_runtime_api::KittiesApi as KittiesRuntimeApi;
+use pallet_kitties_runtime_api::{FamilyTree, Perbill};
+
+/// Kitties RPC methods.
+#[rpc]
+pub trait KittiesApi<BlockHash, KittyIndex> {
+    /// The ancestors of `kitty_id` up to `depth` generations back.
+    #[rpc(name = "kitties_ancestors")]
+    fn ancestors(
+        &self,
+        kitty_id: KittyIndex,
+        depth: u32,
+        at: Option<BlockHash>,
+    ) -> Result<Option<FamilyTree<KittyIndex>>>;
+
+    /// The descendants of `kitty_id` up to `depth` generations down.
+    #[rpc(name = "kitties_descendants")]
+    fn descendants(
+        &self,
+        kitty_id: KittyIndex,
+        depth: u32,
+        at: Option<BlockHash>,
+    ) -> Result<Option<FamilyTree<KittyIndex>>>;
+
+    /// The inbreeding coefficient of `kitty_id` over `depth` generations, in parts per billion.
+    #[rpc(name = "kitties_inbreedingCoefficient")]
+    fn inbreeding_coefficient(
+        &self,
+        kitty_id: KittyIndex,
+        depth: u32,
+        at: Option<BlockHash>,
+    ) -> Result<Option<Perbill>>;
+}
+
+/// An implementation of kitties specific RPC methods.
+pub struct Kitties<C, B> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Kitties<C, B> {
+    /// Create a new `Kitties` with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Kitties { client, _marker: Default::default() }
+    }
+}
+
+/// Error code for failures inside the runtime API call.
+const RUNTIME_ERROR: i64 = 1;
+
+fn runtime_error(e: impl std::fmt::Debug) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(RUNTIME_ERROR),
+        message: "Unable to query the kitties pallet.".into(),
+        data: Some(format!("{:?}", e).into()),
+    }
+}
+
+impl<C, Block, KittyIndex> KittiesApi<<Block as BlockT>::Hash, KittyIndex> for Kitties<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static,
+    C: ProvideRuntimeApi<Block>,
+    C: HeaderBackend<Block>,
+    C::Api: KittiesRuntimeApi<Block, KittyIndex>,
+    KittyIndex: Codec,
+{
+    fn ancestors(
+        &self,
+        kitty_id: KittyIndex,
+        depth: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<FamilyTree<KittyIndex>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.ancestors(&at, kitty_id, depth).map_err(runtime_error)
+    }
+
+    fn descendants(
+        &self,
+        kitty_id: KittyIndex,
+        depth: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<FamilyTree<KittyIndex>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.descendants(&at, kitty_id, depth).map_err(runtime_error)
+    }
+
+    fn inbreeding_coefficient(
+        &self,
+        kitty_id: KittyIndex,
+        depth: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<Perbill>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.inbreeding_coefficient(&at, kitty_id, depth).map_err(runtime_error)
+    }
+}
@@ -0,0 +1,27 @@
+//! Runtime API definition for the kitties pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+
+pub use pallet_kitties::genealogy::{FamilyMember, FamilyTree};
+pub use pallet_kitties::genome::Genome;
+pub use sp_runtime::Perbill;
+
+sp_api::decl_runtime_apis! {
+    /// Queries over the kitties stored by the kitties pallet.
+    pub trait KittiesApi<KittyIndex> where
+        KittyIndex: Codec,
+    {
+        /// Decode raw DNA into its traits, exactly as the runtime does.
+        fn decode_dna(dna: [u8; 16]) -> Genome;
+        /// The decoded traits of a kitty, if it exists.
+        fn kitty_genome(kitty_id: KittyIndex) -> Option<Genome>;
+        /// The ancestors of a kitty up to `depth` generations back, if it exists.
+        fn ancestors(kitty_id: KittyIndex, depth: u32) -> Option<FamilyTree<KittyIndex>>;
+        /// The descendants of a kitty up to `depth` generations down, if it exists.
+        fn descendants(kitty_id: KittyIndex, depth: u32) -> Option<FamilyTree<KittyIndex>>;
+        /// The inbreeding coefficient of a kitty over `depth` generations, if it exists.
+        fn inbreeding_coefficient(kitty_id: KittyIndex, depth: u32) -> Option<Perbill>;
+    }
+}
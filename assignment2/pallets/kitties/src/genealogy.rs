@@ -0,0 +1,169 @@
+//! Family trees and inbreeding coefficients of kitties, for off-chain queries.
+//!
+//! Kitty indices grow with every kitty created, so parents always have lower indices than their
+//! children. The kinship recursion relies on this: of two different kitties, the one with the
+//! higher index cannot be an ancestor of the other.
+//!
+//! Released kitties keep their parent links and stay parents of their children. Family trees leave
+//! them out, but inbreeding coefficients still count them, so releasing an ancestor does not hide
+//! inbreeding.
+
+use super::*;
+use sp_runtime::{Perbill, PerThing};
+use sp_std::collections::btree_map::BTreeMap;
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
+
+/// The most generations looked at, whatever depth is asked for
+pub const MAX_DEPTH: u32 = 32;
+/// The most kitties returned in one family tree
+pub const MAX_TREE_SIZE: usize = 1024;
+
+/// A kitty in a family tree
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct FamilyMember<KittyIndex> {
+    pub kitty_id: KittyIndex,
+    /// Generations between this kitty and the root of the tree
+    pub generation: u32,
+    /// Position in the tree of the kitty this one is a parent or child of, `None` for the root
+    pub relative: Option<u32>,
+}
+
+/// The ancestors or descendants of a kitty, flattened so a tree is drawn from one query
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct FamilyTree<KittyIndex> {
+    /// Kitties in breadth-first order, starting with the root. A kitty reached along several
+    /// lines appears once per line. Released kitties and the relatives reached only through them
+    /// are left out.
+    pub members: Vec<FamilyMember<KittyIndex>>,
+    /// Whether kitties were left out because the tree reached `MAX_TREE_SIZE`
+    pub truncated: bool,
+}
+
+impl<T: Trait> Module<T> {
+    /// The ancestors of a kitty up to `depth` generations back, parents in breeding order
+    pub fn ancestors(kitty_id: T::KittyIndex, depth: u32) -> Option<FamilyTree<T::KittyIndex>> {
+        Self::family_tree(kitty_id, depth, |id| {
+            Self::kitty_parents(id).map(|(parent_1, parent_2)| [parent_1, parent_2].to_vec()).unwrap_or_default()
+        })
+    }
+
+    /// The descendants of a kitty up to `depth` generations down, children by index
+    pub fn descendants(kitty_id: T::KittyIndex, depth: u32) -> Option<FamilyTree<T::KittyIndex>> {
+        Self::family_tree(kitty_id, depth, |id| {
+            let mut children = Self::kitty_children(id);
+            children.sort();
+            children
+        })
+    }
+
+    /// Wright's coefficient of inbreeding of a kitty, looking at most `depth` generations back
+    ///
+    /// This is the chance that both genes the kitty carries for a trait are copies of the same
+    /// gene of a common ancestor. Kitties further back than `depth` count as unrelated founders.
+    pub fn inbreeding_coefficient(kitty_id: T::KittyIndex, depth: u32) -> Option<Perbill> {
+        if !<Kitties<T>>::contains_key(kitty_id) {
+            return None;
+        }
+
+        let pedigree = Self::pedigree(kitty_id, depth.min(MAX_DEPTH));
+        let inbreeding = match pedigree.get(&kitty_id) {
+            Some(&(parent_1, parent_2)) => Self::kinship(parent_1, parent_2, &pedigree, &mut BTreeMap::new()),
+            None => Perbill::zero(),
+        };
+        Some(inbreeding)
+    }
+
+    fn family_tree(
+        kitty_id: T::KittyIndex,
+        depth: u32,
+        relatives: impl Fn(T::KittyIndex) -> Vec<T::KittyIndex>,
+    ) -> Option<FamilyTree<T::KittyIndex>> {
+        if !<Kitties<T>>::contains_key(kitty_id) {
+            return None;
+        }
+
+        let depth = depth.min(MAX_DEPTH);
+        let mut members = Vec::new();
+        members.push(FamilyMember { kitty_id, generation: 0, relative: None });
+        let mut truncated = false;
+
+        let mut next = 0;
+        while next < members.len() {
+            let (id, generation) = (members[next].kitty_id, members[next].generation);
+            if generation < depth {
+                for relative in relatives(id).into_iter().filter(|relative| <Kitties<T>>::contains_key(relative)) {
+                    if members.len() == MAX_TREE_SIZE {
+                        truncated = true;
+                        break;
+                    }
+                    members.push(FamilyMember {
+                        kitty_id: relative,
+                        generation: generation + 1,
+                        relative: Some(next as u32),
+                    });
+                }
+            }
+            next += 1;
+        }
+
+        Some(FamilyTree { members, truncated })
+    }
+
+    /// The parents of a kitty and of its ancestors up to `depth` generations back
+    fn pedigree(kitty_id: T::KittyIndex, depth: u32) -> BTreeMap<T::KittyIndex, (T::KittyIndex, T::KittyIndex)> {
+        let mut pedigree = BTreeMap::new();
+        let mut generation = [kitty_id].to_vec();
+        for _ in 0..depth {
+            let mut parents = Vec::new();
+            for id in generation {
+                if pedigree.contains_key(&id) {
+                    continue;
+                }
+                if let Some((parent_1, parent_2)) = Self::kitty_parents(id) {
+                    pedigree.insert(id, (parent_1, parent_2));
+                    parents.push(parent_1);
+                    parents.push(parent_2);
+                }
+            }
+            generation = parents;
+        }
+        pedigree
+    }
+
+    /// The coefficient of kinship of two kitties: the chance that a gene drawn from each is a copy
+    /// of the same ancestral gene
+    fn kinship(
+        a: T::KittyIndex,
+        b: T::KittyIndex,
+        pedigree: &BTreeMap<T::KittyIndex, (T::KittyIndex, T::KittyIndex)>,
+        memo: &mut BTreeMap<(T::KittyIndex, T::KittyIndex), Perbill>,
+    ) -> Perbill {
+        // The younger kitty cannot be an ancestor of the older one
+        let (younger, older) = if a >= b { (a, b) } else { (b, a) };
+        if let Some(kinship) = memo.get(&(younger, older)) {
+            return *kinship;
+        }
+
+        let kinship = match pedigree.get(&younger) {
+            Some(&(parent_1, parent_2)) if younger == older => {
+                let inbreeding = Self::kinship(parent_1, parent_2, pedigree, memo);
+                Perbill::from_parts((Perbill::ACCURACY + inbreeding.deconstruct()) / 2)
+            }
+            None if younger == older => Perbill::from_percent(50),
+            Some(&(parent_1, parent_2)) => {
+                let kinship_1 = Self::kinship(parent_1, older, pedigree, memo);
+                let kinship_2 = Self::kinship(parent_2, older, pedigree, memo);
+                Perbill::from_parts((kinship_1.deconstruct() + kinship_2.deconstruct()) / 2)
+            }
+            None => Perbill::zero(),
+        };
+
+        memo.insert((younger, older), kinship);
+        kinship
+    }
+}
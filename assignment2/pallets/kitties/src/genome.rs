@@ -0,0 +1,250 @@
+//! Typed view of the 16-byte kitty DNA and the rules by which it is inherited.
+//!
+//! The decoding is deterministic so that the runtime and off-chain clients agree on how a kitty
+//! looks. Byte layout of the DNA:
+//!
+//! | bytes  | trait       | genes                  |
+//! |--------|-------------|------------------------|
+//! | 0, 1   | body colour | dominant, recessive    |
+//! | 2, 3   | pattern     | dominant, recessive    |
+//! | 4, 5   | eye shape   | dominant, recessive    |
+//! | 6, 7   | eye colour  | dominant, recessive    |
+//! | 8      | rarity      | single gene            |
+//! | 9..16  | reserved    | unassigned             |
+//!
+//! A gene selects the variant `gene % variant_count`. Variants are listed in dominance order, so
+//! when a kitty inherits two genes for a trait the one selecting the earlier variant is dominant
+//! and is the one expressed.
+
+use codec::{Encode, Decode};
+use sp_io::hashing::blake2_256;
+use sp_runtime::RuntimeDebug;
+
+/// Raw kitty DNA.
+pub type Dna = [u8; 16];
+
+const BODY_COLOUR: usize = 0;
+const PATTERN: usize = 2;
+const EYE_SHAPE: usize = 4;
+const EYE_COLOUR: usize = 6;
+const RARITY: usize = 8;
+const RESERVED: usize = 9;
+
+/// Chance out of 256 that an inherited gene mutates into a random one.
+const BODY_COLOUR_MUTATION: u8 = 4;
+const PATTERN_MUTATION: u8 = 8;
+const EYE_SHAPE_MUTATION: u8 = 4;
+const EYE_COLOUR_MUTATION: u8 = 6;
+const RARITY_MUTATION: u8 = 2;
+
+/// A trait with a fixed set of variants, most dominant first.
+pub trait Gene: Sized + Copy + 'static {
+    /// All variants in dominance order.
+    const VARIANTS: &'static [Self];
+
+    /// The variant a gene selects.
+    fn from_gene(gene: u8) -> Self {
+        Self::VARIANTS[Self::rank(gene)]
+    }
+
+    /// The dominance rank of a gene, lower is more dominant.
+    fn rank(gene: u8) -> usize {
+        gene as usize % Self::VARIANTS.len()
+    }
+}
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum BodyColour {
+    Black,
+    Ginger,
+    Grey,
+    Cream,
+    White,
+    Lilac,
+}
+
+impl Gene for BodyColour {
+    const VARIANTS: &'static [Self] = &[
+        BodyColour::Black,
+        BodyColour::Ginger,
+        BodyColour::Grey,
+        BodyColour::Cream,
+        BodyColour::White,
+        BodyColour::Lilac,
+    ];
+}
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Pattern {
+    Tabby,
+    Spotted,
+    Bicolour,
+    Solid,
+    Calico,
+}
+
+impl Gene for Pattern {
+    const VARIANTS: &'static [Self] = &[
+        Pattern::Tabby,
+        Pattern::Spotted,
+        Pattern::Bicolour,
+        Pattern::Solid,
+        Pattern::Calico,
+    ];
+}
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum EyeShape {
+    Round,
+    Almond,
+    Sleepy,
+    Wide,
+}
+
+impl Gene for EyeShape {
+    const VARIANTS: &'static [Self] = &[
+        EyeShape::Round,
+        EyeShape::Almond,
+        EyeShape::Sleepy,
+        EyeShape::Wide,
+    ];
+}
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum EyeColour {
+    Brown,
+    Green,
+    Amber,
+    Blue,
+    Odd,
+}
+
+impl Gene for EyeColour {
+    const VARIANTS: &'static [Self] = &[
+        EyeColour::Brown,
+        EyeColour::Green,
+        EyeColour::Amber,
+        EyeColour::Blue,
+        EyeColour::Odd,
+    ];
+}
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Legendary,
+}
+
+impl Rarity {
+    /// Rarity is not a dominance trait: the gene is read as a score with fixed thresholds.
+    pub fn from_gene(gene: u8) -> Self {
+        match gene {
+            0..=191 => Rarity::Common,
+            192..=239 => Rarity::Uncommon,
+            240..=253 => Rarity::Rare,
+            _ => Rarity::Legendary,
+        }
+    }
+}
+
+/// A trait as carried by a kitty: the expressed gene and the hidden one.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct Allele<G> {
+    pub dominant: G,
+    pub recessive: G,
+}
+
+/// The decoded DNA of a kitty.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct Genome {
+    pub body_colour: Allele<BodyColour>,
+    pub pattern: Allele<Pattern>,
+    pub eye_shape: Allele<EyeShape>,
+    pub eye_colour: Allele<EyeColour>,
+    pub rarity: Rarity,
+}
+
+fn allele<G: Gene>(dna: &Dna, at: usize) -> Allele<G> {
+    Allele { dominant: G::from_gene(dna[at]), recessive: G::from_gene(dna[at + 1]) }
+}
+
+/// Decode DNA into its traits.
+pub fn decode(dna: &Dna) -> Genome {
+    Genome {
+        body_colour: allele(dna, BODY_COLOUR),
+        pattern: allele(dna, PATTERN),
+        eye_shape: allele(dna, EYE_SHAPE),
+        eye_colour: allele(dna, EYE_COLOUR),
+        rarity: Rarity::from_gene(dna[RARITY]),
+    }
+}
+
+/// Random bytes consumed one at a time while breeding.
+struct Rolls {
+    bytes: [u8; 32],
+    next: usize,
+}
+
+impl Rolls {
+    fn new(seed: &Dna) -> Self {
+        Rolls { bytes: blake2_256(seed), next: 0 }
+    }
+
+    fn roll(&mut self) -> u8 {
+        let byte = self.bytes[self.next];
+        self.next += 1;
+        if self.next == self.bytes.len() {
+            self.bytes = blake2_256(&self.bytes);
+            self.next = 0;
+        }
+        byte
+    }
+
+    /// Pass `gene` on unchanged, or a random gene with a chance of `rate` out of 256.
+    fn mutate(&mut self, gene: u8, rate: u8) -> u8 {
+        let chance = self.roll();
+        let replacement = self.roll();
+        if chance < rate { replacement } else { gene }
+    }
+}
+
+/// Mendelian inheritance: one gene from each parent, ordered by dominance.
+fn inherit_allele<G: Gene>(child: &mut Dna, a: &Dna, b: &Dna, at: usize, rate: u8, rolls: &mut Rolls) {
+    let pick_a = (rolls.roll() & 1) as usize;
+    let from_a = rolls.mutate(a[at + pick_a], rate);
+    let pick_b = (rolls.roll() & 1) as usize;
+    let from_b = rolls.mutate(b[at + pick_b], rate);
+    // The first parent's gene wins ties so the order of the genes stays deterministic.
+    if G::rank(from_b) < G::rank(from_a) {
+        child[at] = from_b;
+        child[at + 1] = from_a;
+    } else {
+        child[at] = from_a;
+        child[at + 1] = from_b;
+    }
+}
+
+/// Combine the DNA of two parents using `seed` as the source of randomness.
+pub fn inherit(a: &Dna, b: &Dna, seed: &Dna) -> Dna {
+    let mut rolls = Rolls::new(seed);
+    let mut child = [0u8; 16];
+
+    inherit_allele::<BodyColour>(&mut child, a, b, BODY_COLOUR, BODY_COLOUR_MUTATION, &mut rolls);
+    inherit_allele::<Pattern>(&mut child, a, b, PATTERN, PATTERN_MUTATION, &mut rolls);
+    inherit_allele::<EyeShape>(&mut child, a, b, EYE_SHAPE, EYE_SHAPE_MUTATION, &mut rolls);
+    inherit_allele::<EyeColour>(&mut child, a, b, EYE_COLOUR, EYE_COLOUR_MUTATION, &mut rolls);
+
+    // Rarity comes from one parent as a whole, and rarely mutates.
+    let parent = if rolls.roll() & 1 == 0 { a } else { b };
+    child[RARITY] = rolls.mutate(parent[RARITY], RARITY_MUTATION);
+
+    // Unassigned bytes are mixed bit by bit so they stay available for future traits.
+    for (i, byte) in child.iter_mut().enumerate().skip(RESERVED) {
+        let selector = rolls.roll();
+        *byte = (selector & a[i]) | (!selector & b[i]);
+    }
+
+    child
+}
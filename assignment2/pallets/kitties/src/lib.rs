@@ -1,26 +1,101 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Encode, Decode};
-use frame_support::{decl_module, decl_storage, decl_error, decl_event, ensure, StorageValue, StorageMap, Parameter};
-use frame_support::traits::{Currency, ReservableCurrency, Randomness, Get, Vec};
+use frame_support::{decl_module, decl_storage, decl_error, decl_event, ensure, StorageValue, StorageMap, StorageDoubleMap,
+    IterableStorageDoubleMap, Parameter, transactional};
+use frame_support::storage::with_transaction;
+use frame_support::traits::{Currency, ExistenceRequirement, ReservableCurrency, Randomness, Get, Vec};
+use frame_support::weights::Weight;
 use sp_io::hashing::blake2_128;
 use frame_system::ensure_signed;
-use sp_runtime::{DispatchError, DispatchResult};
-use sp_runtime::traits::{AtLeast32Bit, Bounded, Member};
+use sp_runtime::{DispatchError, DispatchResult, RuntimeDebug, TransactionOutcome};
+use sp_runtime::traits::{AtLeast32Bit, Bounded, Hash as HashT, MaybeSerializeDeserialize, Member, Saturating};
 use sp_std::convert::TryInto;
 
+pub mod genealogy;
+pub mod genome;
+pub mod migrations;
+pub mod nft;
+
 #[derive(Encode, Decode)]
 pub struct Kitty(pub [u8; 16]);
 
+impl Kitty {
+    /// The traits encoded in the DNA of this kitty
+    pub fn genome(&self) -> genome::Genome {
+        genome::decode(&self.0)
+    }
+}
+
+/// Breeding state of a kitty, kept apart from its DNA
+///
+/// Kitties without an entry are generation 0 and off cooldown.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct BreedingInfo<BlockNumber> {
+    /// 0 for created kitties, one more than the older parent for bred ones
+    pub generation: u32,
+    /// The first block at which the kitty may breed again
+    pub cooldown_until: BlockNumber,
+}
+
+/// A pending commitment to breed, revealed in a later block
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct BreedCommitment<Hash, BlockNumber, Balance> {
+    /// The hash of the parents, the maximum sire fee and a salt
+    pub hash: Hash,
+    /// The block the commitment was made in
+    pub committed_at: BlockNumber,
+    /// The deposit reserved from the breeder, forfeited if the commitment is not revealed in time
+    pub deposit: Balance,
+}
+
+/// A running English auction of a kitty
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Auction<AccountId, Balance, BlockNumber> {
+    /// The owner selling the kitty
+    pub seller: AccountId,
+    /// The lowest bid the seller accepts
+    pub reserve_price: Balance,
+    /// The block at whose end the auction is settled
+    pub end: BlockNumber,
+    /// The highest bid so far and who placed it
+    pub best_bid: Option<(AccountId, Balance)>,
+}
+
+/// The storage layout versions of this pallet
+#[derive(Encode, Decode, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+pub enum Releases {
+    /// Owned kitties and relationships stored as one `Vec` per account or kitty
+    #[default]
+    V1,
+    /// Owned kitties and relationships stored as double maps with count trackers
+    V2,
+}
+
 type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+type AuctionOf<T> = Auction<<T as frame_system::Trait>::AccountId, BalanceOf<T>, <T as frame_system::Trait>::BlockNumber>;
 
 pub trait Trait: frame_system::Trait {
     /// Because this pallet emits events, it depends on the runtime's definition of an event.
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
     type Randomness: Randomness<Self::Hash>;
-    type KittyIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy + TryInto<u32>;
+    type KittyIndex: Parameter + Member + AtLeast32Bit + Bounded + Default + Copy + TryInto<u32> + MaybeSerializeDeserialize;
     type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
     type DepositValue: Get<u32>;
+    /// The maximum number of auctions that may end in the same block
+    type MaxAuctionsPerBlock: Get<u32>;
+    /// The cooldown after breeding of a generation 0 kitty, growing by this much per generation
+    type BaseCooldown: Get<Self::BlockNumber>;
+    /// The longest cooldown after breeding, whatever the generation
+    type MaxCooldown: Get<Self::BlockNumber>;
+    /// Whether breeding must go through `commit_breed` and `reveal_breed` instead of `breed`
+    type CommitRevealBreeding: Get<bool>;
+    /// The blocks between a breeding commitment and the block whose hash seeds its reveal, at least 1
+    type RevealDelay: Get<Self::BlockNumber>;
+    /// The deposit reserved with a breeding commitment, returned when it is revealed
+    type CommitDeposit: Get<u32>;
+    /// The prefix of kitty metadata URIs, followed by the kitty index
+    type TokenUriBase: Get<Vec<u8>>;
 }
 
 decl_storage! {
@@ -31,16 +106,61 @@ decl_storage! {
         pub KittiesCount get(fn kitties_count): T::KittyIndex;
         /// Get the kitty owner by kitty id
         pub KittyOwner get(fn kitty_owner): map hasher(blake2_128_concat) T::KittyIndex => Option<T::AccountId>;
-        /// Get kitties owned by account ID
-        pub OwnedKitties get(fn owned_kitties): map hasher(blake2_128_concat) T::AccountId => Vec<T::KittyIndex>;
+        /// Set of kitties owned by account ID, listed by `owned_kitties`
+        pub OwnedKitties: double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) T::KittyIndex => Option<()>;
+        /// Get the number of kitties owned by account ID
+        pub OwnedKittiesCount get(fn owned_kitties_count): map hasher(blake2_128_concat) T::AccountId => u32;
         /// Get parent IDs by kitty index
         pub KittyParents get(fn kitty_parents): map hasher(blake2_128_concat) T::KittyIndex => Option<(T::KittyIndex, T::KittyIndex)>;
-        /// Get sibling IDs by kitty index
-        pub KittySiblings get(fn kitty_siblings): map hasher(blake2_128_concat) T::KittyIndex => Vec<T::KittyIndex>;
-        /// Get child IDs by kitty index
-        pub KittyChildren get(fn kitty_children): map hasher(blake2_128_concat) T::KittyIndex => Vec<T::KittyIndex>;
-        /// Get partner ID by kitty index
-        pub KittyPartners get(fn kitty_partners): map hasher(blake2_128_concat) T::KittyIndex => Vec<T::KittyIndex>;
+        /// Set of child IDs by parent index, listed by `kitty_children`
+        pub KittyChildren: double_map hasher(blake2_128_concat) T::KittyIndex, hasher(blake2_128_concat) T::KittyIndex => Option<()>;
+        /// Get the number of children by parent index
+        pub KittyChildrenCount get(fn kitty_children_count): map hasher(blake2_128_concat) T::KittyIndex => u32;
+        /// Set of partner IDs by kitty index, listed by `kitty_partners`
+        pub KittyPartners: double_map hasher(blake2_128_concat) T::KittyIndex, hasher(blake2_128_concat) T::KittyIndex => Option<()>;
+        /// Get the number of partners by kitty index
+        pub KittyPartnersCount get(fn kitty_partners_count): map hasher(blake2_128_concat) T::KittyIndex => u32;
+        /// Get the generation and breeding cooldown by kitty index
+        pub KittyBreeding get(fn kitty_breeding): map hasher(blake2_128_concat) T::KittyIndex => BreedingInfo<T::BlockNumber>;
+        /// Get the fee asked for breeding with a kitty of another owner
+        pub SireOffers get(fn sire_offer): map hasher(blake2_128_concat) T::KittyIndex => Option<BalanceOf<T>>;
+        /// Get the pending breeding commitment of an account
+        pub BreedCommitments get(fn breed_commitment):
+            map hasher(blake2_128_concat) T::AccountId => Option<BreedCommitment<T::Hash, T::BlockNumber, BalanceOf<T>>>;
+        /// Get the account approved to transfer a kitty for its owner
+        pub KittyApprovals get(fn kitty_approval): map hasher(blake2_128_concat) T::KittyIndex => Option<T::AccountId>;
+        /// Whether an operator may transfer and approve every kitty of an owner, by owner and operator
+        pub KittyOperators get(fn is_operator):
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) T::AccountId => bool;
+        /// Get the asking price of kitties listed for sale
+        pub KittyPrices get(fn kitty_price): map hasher(blake2_128_concat) T::KittyIndex => Option<BalanceOf<T>>;
+        /// Get the running auction of a kitty
+        pub Auctions get(fn auction): map hasher(blake2_128_concat) T::KittyIndex => Option<AuctionOf<T>>;
+        /// Get the kitties whose auctions end at a block, bounded by `MaxAuctionsPerBlock`
+        pub AuctionsEndingAt get(fn auctions_ending_at): map hasher(twox_64_concat) T::BlockNumber => Vec<T::KittyIndex>;
+        /// The storage layout version, used to decide which migrations to run on upgrade
+        StorageVersion build(|_| Releases::V2): Releases;
+    }
+    add_extra_genesis {
+        /// Kitties minted at genesis in index order: owner, DNA and optionally the indices of both parents
+        config(kitties): Vec<(T::AccountId, [u8; 16], Option<(T::KittyIndex, T::KittyIndex)>)>;
+        build(|config: &GenesisConfig<T>| {
+            for (owner, dna, parents) in &config.kitties {
+                let kitty_id = <Module<T>>::next_kitty_id().expect("too many genesis kitties");
+                <Module<T>>::insert_kitty(owner, kitty_id, Kitty(*dna))
+                    .expect("genesis kitty owners must be able to reserve the deposit");
+
+                if let Some((parent_1, parent_2)) = *parents {
+                    assert!(parent_1 != parent_2, "genesis kitty parents must differ");
+                    assert!(parent_1 < kitty_id && parent_2 < kitty_id, "genesis kitty parents must come first");
+                    let generation = <Module<T>>::kitty_breeding(parent_1).generation
+                        .max(<Module<T>>::kitty_breeding(parent_2).generation)
+                        .saturating_add(1);
+                    <KittyBreeding<T>>::insert(kitty_id, BreedingInfo { generation, cooldown_until: Default::default() });
+                    <Module<T>>::record_parents(kitty_id, parent_1, parent_2);
+                }
+            }
+        });
     }
 }
 
@@ -51,17 +171,76 @@ decl_error! {
         RequireDifferentParent,
         NotValidOwner,
         NotValidReceiver,
+        NotForSale,
+        PriceTooHigh,
+        KittyInAuction,
+        NoSuchAuction,
+        InvalidAuctionEnd,
+        TooManyAuctions,
+        TooManyRelatives,
+        AuctionEnded,
+        BidTooLow,
+        AuctionHasBids,
+        KittyOnCooldown,
+        NoSireOffer,
+        NotApproved,
+        CommitRevealRequired,
+        CommitRevealDisabled,
+        NoBreedCommitment,
+        InvalidReveal,
+        RevealTooEarly,
+        RevealExpired,
+        BreedCommitmentPending,
+        CommitmentNotExpired,
     }
 }
 
 decl_event! {
-    pub enum Event<T> where AccountId = <T as frame_system::Trait>::AccountId, KittyIndex = <T as Trait>::KittyIndex {
+    pub enum Event<T> where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        KittyIndex = <T as Trait>::KittyIndex,
+        Balance = BalanceOf<T>,
+        BlockNumber = <T as frame_system::Trait>::BlockNumber,
+        Hash = <T as frame_system::Trait>::Hash,
+    {
         /// Event emitted when a kitty is created. [who, index]
         Created(AccountId, KittyIndex),
         /// Event emitted when a kitty is transferred. [from, to, index]
         Transferred(AccountId, AccountId, KittyIndex),
+        /// Event emitted when a kitty is released and its deposit unreserved. [owner, index]
+        Released(AccountId, KittyIndex),
+        /// Event emitted when the approval to transfer a kitty changes. [owner, index, approved]
+        ApprovalSet(AccountId, KittyIndex, Option<AccountId>),
+        /// Event emitted when an operator is approved or revoked for all kitties of an owner. [owner, operator, approved]
+        OperatorSet(AccountId, AccountId, bool),
         /// Event emitted when a kitty is born. [who, idx1, idx2, new_idx]
         Breeded(AccountId, KittyIndex, KittyIndex, KittyIndex),
+        /// Event emitted when an owner offers a kitty as sire. [owner, index, fee]
+        SireOffered(AccountId, KittyIndex, Balance),
+        /// Event emitted when an account commits to breed. [who, hash, committed_at]
+        BreedCommitted(AccountId, Hash, BlockNumber),
+        /// Event emitted when an expired breeding commitment is removed and its deposit forfeited. [who, deposit]
+        BreedCommitmentForfeited(AccountId, Balance),
+        /// Event emitted when a sire offer is withdrawn. [owner, index]
+        SireOfferCancelled(AccountId, KittyIndex),
+        /// Event emitted when a sire fee is paid for breeding. [who, sire_owner, sire_index, fee]
+        SireFeePaid(AccountId, AccountId, KittyIndex, Balance),
+        /// Event emitted when a kitty is listed for sale. [owner, index, price]
+        PriceSet(AccountId, KittyIndex, Balance),
+        /// Event emitted when a kitty is taken off sale. [owner, index]
+        PriceCleared(AccountId, KittyIndex),
+        /// Event emitted when a kitty is sold. [seller, buyer, index, price]
+        Sold(AccountId, AccountId, KittyIndex, Balance),
+        /// Event emitted when an auction starts. [seller, index, reserve_price, end]
+        AuctionStarted(AccountId, KittyIndex, Balance, BlockNumber),
+        /// Event emitted when a bid becomes the highest. [bidder, index, amount]
+        BidPlaced(AccountId, KittyIndex, Balance),
+        /// Event emitted when a bid is outbid and its funds are unreserved. [bidder, index, amount]
+        Outbid(AccountId, KittyIndex, Balance),
+        /// Event emitted when an auction is settled. [seller, winner, index, price]
+        AuctionSettled(AccountId, AccountId, KittyIndex, Balance),
+        /// Event emitted when an auction ends without a sale. [seller, index]
+        AuctionCancelled(AccountId, KittyIndex),
     }
 }
 
@@ -72,6 +251,26 @@ decl_module! {
         // Events must be initialized if they are used by the pallet.
         fn deposit_event() = default;
 
+        /// Migrate storage written by an older version of this pallet
+        fn on_runtime_upgrade() -> Weight {
+            migrations::migrate_to_v2::<T>()
+        }
+
+        /// Account for the auctions settled at the end of this block
+        ///
+        /// Settling an auction takes it, unreserves and pays the winning bid and moves the kitty.
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            let ending = <AuctionsEndingAt<T>>::decode_len(now).unwrap_or(0) as Weight;
+            T::DbWeight::get().reads_writes(2 + 9 * ending, 1 + 14 * ending)
+        }
+
+        /// Settle the auctions ending at this block
+        fn on_finalize(now: T::BlockNumber) {
+            for kitty_id in <AuctionsEndingAt<T>>::take(now) {
+                Self::settle_auction(kitty_id);
+            }
+        }
+
         /// Create a new kitty
         #[weight = 0]
         pub fn create(origin) -> DispatchResult {
@@ -98,29 +297,327 @@ decl_module! {
             // !!!
             ensure!(sender == owner, Error::<T>::NotValidOwner);
             ensure!(sender != to, Error::<T>::NotValidReceiver);
+            ensure!(!<Auctions<T>>::contains_key(kitty_id), Error::<T>::KittyInAuction);
 
-            Self::remove_kitty_from_owner(&sender, kitty_id)?;
-            Self::add_kitty_to_owner(&to, kitty_id)?;
-            <KittyOwner<T>>::insert(kitty_id, to.clone());
+            Self::do_transfer(&sender, &to, kitty_id)?;
             Self::deposit_event(RawEvent::Transferred(sender, to, kitty_id));
 
             Ok(())
         }
 
+        /// Let `spender` transfer a kitty until its next transfer, or clear the approval with `None`
+        ///
+        /// Operators of the owner may approve on its behalf.
+        #[weight = T::DbWeight::get().reads_writes(3, 1)]
+        pub fn approve(origin, kitty_id: T::KittyIndex, spender: Option<T::AccountId>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+            ensure!(sender == owner || Self::is_operator(&owner, &sender), Error::<T>::NotValidOwner);
+
+            <Self as nft::NonFungibleAsset<T::AccountId>>::approve(&owner, &kitty_id, spender)
+        }
+
+        /// Approve or revoke `operator` to transfer and approve all kitties of the sender
+        #[weight = T::DbWeight::get().reads_writes(0, 1)]
+        pub fn set_approval_for_all(origin, operator: T::AccountId, approved: bool) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            ensure!(sender != operator, Error::<T>::NotValidReceiver);
+
+            if approved {
+                <KittyOperators<T>>::insert(&sender, &operator, true);
+            } else {
+                <KittyOperators<T>>::remove(&sender, &operator);
+            }
+            Self::deposit_event(RawEvent::OperatorSet(sender, operator, approved));
+
+            Ok(())
+        }
+
+        /// Transfer a kitty of `from` as its owner, its approved spender or an operator of `from`
+        #[weight = T::DbWeight::get().reads_writes(10, 10)]
+        pub fn transfer_from(origin, from: T::AccountId, to: T::AccountId, kitty_id: T::KittyIndex) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+            ensure!(from == owner, Error::<T>::NotValidOwner);
+            ensure!(
+                sender == owner
+                    || Self::kitty_approval(kitty_id).as_ref() == Some(&sender)
+                    || Self::is_operator(&owner, &sender),
+                Error::<T>::NotApproved
+            );
+
+            <Self as nft::NonFungibleAsset<T::AccountId>>::transfer(&from, &to, &kitty_id)
+        }
+
+        /// Release a kitty, destroying it and unreserving its deposit
+        ///
+        /// The kitty leaves the children of its parents and the partners of its partners. Its own
+        /// parents and its children's parent links are kept, so the lineage and inbreeding of its
+        /// descendants do not change.
+        ///
+        /// `relatives` is an upper bound on the kitty's partners plus children, which the weight is
+        /// charged for.
+        #[weight = T::DbWeight::get().reads_writes(10 + 2 * *relatives as Weight, 16 + 2 * *relatives as Weight)]
+        pub fn release(origin, kitty_id: T::KittyIndex, relatives: u32) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+            ensure!(sender == owner, Error::<T>::NotValidOwner);
+            ensure!(!<Auctions<T>>::contains_key(kitty_id), Error::<T>::KittyInAuction);
+            ensure!(Self::relatives_count(kitty_id) <= relatives, Error::<T>::TooManyRelatives);
+
+            Self::remove_kitty_from_owner(&sender, kitty_id)?;
+            Self::remove_kitty(kitty_id);
+            Self::deposit_event(RawEvent::Released(sender, kitty_id));
+
+            Ok(())
+        }
+
+        /// List a kitty for sale, or change its price
+        #[weight = T::DbWeight::get().reads_writes(2, 1)]
+        pub fn set_price(origin, kitty_id: T::KittyIndex, price: BalanceOf<T>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+            ensure!(sender == owner, Error::<T>::NotValidOwner);
+            ensure!(!<Auctions<T>>::contains_key(kitty_id), Error::<T>::KittyInAuction);
+
+            <KittyPrices<T>>::insert(kitty_id, price);
+            Self::deposit_event(RawEvent::PriceSet(sender, kitty_id, price));
+
+            Ok(())
+        }
+
+        /// Take a kitty off sale
+        #[weight = T::DbWeight::get().reads_writes(2, 1)]
+        pub fn clear_price(origin, kitty_id: T::KittyIndex) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+            ensure!(sender == owner, Error::<T>::NotValidOwner);
+            ensure!(<KittyPrices<T>>::contains_key(kitty_id), Error::<T>::NotForSale);
+
+            <KittyPrices<T>>::remove(kitty_id);
+            Self::deposit_event(RawEvent::PriceCleared(sender, kitty_id));
+
+            Ok(())
+        }
+
+        /// Buy a kitty listed for sale, paying at most `max_price`
+        ///
+        /// The buyer pays the asking price to the seller and takes over the deposit of the kitty.
+        #[weight = T::DbWeight::get().reads_writes(9, 12)]
+        #[transactional]
+        pub fn buy(origin, kitty_id: T::KittyIndex, max_price: BalanceOf<T>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+            ensure!(sender != owner, Error::<T>::NotValidReceiver);
+            let price = Self::kitty_price(kitty_id).ok_or(Error::<T>::NotForSale)?;
+            ensure!(price <= max_price, Error::<T>::PriceTooHigh);
+
+            T::Currency::transfer(&sender, &owner, price, ExistenceRequirement::KeepAlive)?;
+            Self::do_transfer(&owner, &sender, kitty_id)?;
+            Self::deposit_event(RawEvent::Sold(owner, sender, kitty_id, price));
+
+            Ok(())
+        }
+
+        /// Start an English auction of a kitty, settled at the end of block `end`
+        ///
+        /// The kitty is taken off sale and cannot be transferred until the auction ends.
+        #[weight = T::DbWeight::get().reads_writes(3, 3)]
+        pub fn create_auction(
+            origin,
+            kitty_id: T::KittyIndex,
+            reserve_price: BalanceOf<T>,
+            end: T::BlockNumber,
+        ) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+            ensure!(sender == owner, Error::<T>::NotValidOwner);
+            ensure!(!<Auctions<T>>::contains_key(kitty_id), Error::<T>::KittyInAuction);
+            ensure!(end > <frame_system::Module<T>>::block_number(), Error::<T>::InvalidAuctionEnd);
+
+            <AuctionsEndingAt<T>>::try_mutate(end, |ending| -> DispatchResult {
+                ensure!(ending.len() < T::MaxAuctionsPerBlock::get() as usize, Error::<T>::TooManyAuctions);
+                ending.push(kitty_id);
+                Ok(())
+            })?;
+            <Auctions<T>>::insert(kitty_id, Auction { seller: sender.clone(), reserve_price, end, best_bid: None });
+            <KittyPrices<T>>::remove(kitty_id);
+            Self::deposit_event(RawEvent::AuctionStarted(sender, kitty_id, reserve_price, end));
+
+            Ok(())
+        }
+
+        /// Bid on a running auction
+        ///
+        /// The bid and the deposit of the kitty are reserved until the bid is outbid or the auction ends.
+        #[weight = T::DbWeight::get().reads_writes(3, 3)]
+        #[transactional]
+        pub fn bid(origin, kitty_id: T::KittyIndex, amount: BalanceOf<T>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let mut auction = Self::auction(kitty_id).ok_or(Error::<T>::NoSuchAuction)?;
+            ensure!(sender != auction.seller, Error::<T>::NotValidReceiver);
+            ensure!(<frame_system::Module<T>>::block_number() <= auction.end, Error::<T>::AuctionEnded);
+            ensure!(amount >= auction.reserve_price, Error::<T>::BidTooLow);
+
+            if let Some((bidder, best)) = auction.best_bid.take() {
+                ensure!(amount > best, Error::<T>::BidTooLow);
+                T::Currency::unreserve(&bidder, best.saturating_add(Self::deposit_value()));
+                Self::deposit_event(RawEvent::Outbid(bidder, kitty_id, best));
+            }
+            T::Currency::reserve(&sender, amount.saturating_add(Self::deposit_value()))?;
+
+            auction.best_bid = Some((sender.clone(), amount));
+            <Auctions<T>>::insert(kitty_id, auction);
+            Self::deposit_event(RawEvent::BidPlaced(sender, kitty_id, amount));
+
+            Ok(())
+        }
+
+        /// Cancel an auction that has no bids yet
+        #[weight = T::DbWeight::get().reads_writes(2, 2)]
+        pub fn cancel_auction(origin, kitty_id: T::KittyIndex) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let auction = Self::auction(kitty_id).ok_or(Error::<T>::NoSuchAuction)?;
+            ensure!(sender == auction.seller, Error::<T>::NotValidOwner);
+            ensure!(auction.best_bid.is_none(), Error::<T>::AuctionHasBids);
+
+            <Auctions<T>>::remove(kitty_id);
+            <AuctionsEndingAt<T>>::mutate_exists(auction.end, |maybe_ending| {
+                if let Some(ending) = maybe_ending {
+                    ending.retain(|id| *id != kitty_id);
+                    if ending.is_empty() {
+                        *maybe_ending = None;
+                    }
+                }
+            });
+            Self::deposit_event(RawEvent::AuctionCancelled(sender, kitty_id));
+
+            Ok(())
+        }
+
         /// Breed kitties
-        #[weight = 0]
-        pub fn breed(origin, kitty_id_1: T::KittyIndex, kitty_id_2: T::KittyIndex) -> DispatchResult {
+        ///
+        /// The sender must own `kitty_id_1`. `kitty_id_2` is either also theirs or offered as sire by its
+        /// owner, who is then paid the sire fee, which must not exceed `max_sire_fee`. Not available when
+        /// `CommitRevealBreeding` is set.
+        #[weight = T::DbWeight::get().reads_writes(19, 20)]
+        #[transactional]
+        pub fn breed(
+            origin,
+            kitty_id_1: T::KittyIndex,
+            kitty_id_2: T::KittyIndex,
+            max_sire_fee: BalanceOf<T>,
+        ) -> DispatchResult {
             let sender = ensure_signed(origin)?;
+            ensure!(!T::CommitRevealBreeding::get(), Error::<T>::CommitRevealRequired);
 
-            Self::do_breed(sender, kitty_id_1, kitty_id_2)?;
+            let seed = Self::random_value(&sender);
+            Self::breed_with(sender, kitty_id_1, kitty_id_2, max_sire_fee, seed)?;
 
             Ok(())
         }
-    }
-}
 
-fn combine_dna(dna1: u8, dna2: u8, selector: u8) -> u8 {
-    (selector & dna1) | (!selector & dna2)
+        /// Commit to breeding, to be revealed after `RevealDelay` blocks
+        ///
+        /// `hash` is the hash of `(kitty_id_1, kitty_id_2, max_sire_fee, salt)` as later passed to
+        /// `reveal_breed`. `CommitDeposit` is reserved until the reveal, and forfeited if the reveal
+        /// never comes, so a breeder cannot drop an outcome they dislike for free. An account has at
+        /// most one pending commitment.
+        #[weight = T::DbWeight::get().reads_writes(2, 2)]
+        pub fn commit_breed(origin, hash: T::Hash) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            ensure!(T::CommitRevealBreeding::get(), Error::<T>::CommitRevealDisabled);
+            ensure!(!<BreedCommitments<T>>::contains_key(&sender), Error::<T>::BreedCommitmentPending);
+
+            let deposit = BalanceOf::<T>::from(T::CommitDeposit::get());
+            T::Currency::reserve(&sender, deposit)?;
+            let committed_at = <frame_system::Module<T>>::block_number();
+            <BreedCommitments<T>>::insert(&sender, BreedCommitment { hash, committed_at, deposit });
+            Self::deposit_event(RawEvent::BreedCommitted(sender, hash, committed_at));
+
+            Ok(())
+        }
+
+        /// Breed the kitties of the pending commitment, as `breed` does
+        ///
+        /// The DNA is derived from the salt and the hash of the block `RevealDelay` blocks after the
+        /// commitment, which neither the breeder nor a block author knows when committing. The reveal
+        /// must come after that block and while its hash is still kept, and returns the deposit.
+        #[weight = T::DbWeight::get().reads_writes(21, 22)]
+        #[transactional]
+        pub fn reveal_breed(
+            origin,
+            kitty_id_1: T::KittyIndex,
+            kitty_id_2: T::KittyIndex,
+            max_sire_fee: BalanceOf<T>,
+            salt: [u8; 32],
+        ) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            ensure!(T::CommitRevealBreeding::get(), Error::<T>::CommitRevealDisabled);
+            let commitment = <BreedCommitments<T>>::take(&sender).ok_or(Error::<T>::NoBreedCommitment)?;
+            let hash = T::Hashing::hash_of(&(kitty_id_1, kitty_id_2, max_sire_fee, salt));
+            ensure!(hash == commitment.hash, Error::<T>::InvalidReveal);
+
+            let seed_block = Self::seed_block(&commitment);
+            ensure!(<frame_system::Module<T>>::block_number() > seed_block, Error::<T>::RevealTooEarly);
+            let seed_hash = <frame_system::Module<T>>::block_hash(seed_block);
+            ensure!(seed_hash != T::Hash::default(), Error::<T>::RevealExpired);
+
+            T::Currency::unreserve(&sender, commitment.deposit);
+            let seed = (seed_hash, salt).using_encoded(blake2_128);
+            Self::breed_with(sender, kitty_id_1, kitty_id_2, max_sire_fee, seed)?;
+
+            Ok(())
+        }
+
+        /// Remove a commitment that can no longer be revealed, forfeiting its deposit
+        ///
+        /// Anyone may call this once the hash of the commitment's seed block is no longer kept.
+        #[weight = T::DbWeight::get().reads_writes(4, 3)]
+        pub fn remove_expired_commitment(origin, who: T::AccountId) -> DispatchResult {
+            ensure_signed(origin)?;
+            let commitment = Self::breed_commitment(&who).ok_or(Error::<T>::NoBreedCommitment)?;
+            let seed_block = Self::seed_block(&commitment);
+            ensure!(
+                <frame_system::Module<T>>::block_number() > seed_block
+                    && <frame_system::Module<T>>::block_hash(seed_block) == T::Hash::default(),
+                Error::<T>::CommitmentNotExpired
+            );
+
+            <BreedCommitments<T>>::remove(&who);
+            let (_, not_slashed) = T::Currency::slash_reserved(&who, commitment.deposit);
+            Self::deposit_event(RawEvent::BreedCommitmentForfeited(who, commitment.deposit.saturating_sub(not_slashed)));
+
+            Ok(())
+        }
+
+        /// Let other owners breed with a kitty for a fee
+        #[weight = T::DbWeight::get().reads_writes(1, 1)]
+        pub fn offer_sire(origin, kitty_id: T::KittyIndex, fee: BalanceOf<T>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+            ensure!(sender == owner, Error::<T>::NotValidOwner);
+
+            <SireOffers<T>>::insert(kitty_id, fee);
+            Self::deposit_event(RawEvent::SireOffered(sender, kitty_id, fee));
+
+            Ok(())
+        }
+
+        /// Withdraw a sire offer
+        #[weight = T::DbWeight::get().reads_writes(2, 1)]
+        pub fn cancel_sire_offer(origin, kitty_id: T::KittyIndex) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+            ensure!(sender == owner, Error::<T>::NotValidOwner);
+            ensure!(<SireOffers<T>>::contains_key(kitty_id), Error::<T>::NoSireOffer);
+
+            <SireOffers<T>>::remove(kitty_id);
+            Self::deposit_event(RawEvent::SireOfferCancelled(sender, kitty_id));
+
+            Ok(())
+        }
+    }
 }
 
 impl<T: Trait> Module<T> {
@@ -133,6 +630,91 @@ impl<T: Trait> Module<T> {
         payload.using_encoded(blake2_128)
     }
 
+    /// The kitties owned by an account, in no particular order
+    pub fn owned_kitties(owner: &T::AccountId) -> Vec<T::KittyIndex> {
+        <OwnedKitties<T>>::iter_prefix(owner).map(|(kitty_id, ())| kitty_id).collect()
+    }
+
+    /// The children of a kitty, in no particular order
+    pub fn kitty_children(kitty_id: T::KittyIndex) -> Vec<T::KittyIndex> {
+        <KittyChildren<T>>::iter_prefix(kitty_id).map(|(child, ())| child).collect()
+    }
+
+    /// The kitties sharing at least one parent with a kitty, by index
+    ///
+    /// Siblings are read from the children of the kitty's parents rather than stored, so breeding
+    /// does not write to every earlier child of the parents.
+    pub fn kitty_siblings(kitty_id: T::KittyIndex) -> Vec<T::KittyIndex> {
+        let (parent_1, parent_2) = match Self::kitty_parents(kitty_id) {
+            Some(parents) => parents,
+            None => return Vec::new(),
+        };
+        let mut siblings: Vec<T::KittyIndex> = <KittyChildren<T>>::iter_prefix(parent_1)
+            .chain(<KittyChildren<T>>::iter_prefix(parent_2))
+            .map(|(sibling, ())| sibling)
+            .filter(|sibling| *sibling != kitty_id)
+            .collect();
+        siblings.sort();
+        siblings.dedup();
+        siblings
+    }
+
+    /// The kitties a kitty has been bred with, in no particular order
+    pub fn kitty_partners(kitty_id: T::KittyIndex) -> Vec<T::KittyIndex> {
+        <KittyPartners<T>>::iter_prefix(kitty_id).map(|(partner, ())| partner).collect()
+    }
+
+    /// The decoded traits of a kitty
+    pub fn kitty_genome(kitty_id: T::KittyIndex) -> Option<genome::Genome> {
+        Self::kitties(kitty_id).map(|kitty| kitty.genome())
+    }
+
+    /// The cooldown after breeding a kitty of the given generation
+    pub fn cooldown(generation: u32) -> T::BlockNumber {
+        let cooldown = T::BaseCooldown::get().saturating_mul(T::BlockNumber::from(generation.saturating_add(1)));
+        cooldown.min(T::MaxCooldown::get())
+    }
+
+    /// The block whose hash seeds the reveal of a breeding commitment
+    fn seed_block(commitment: &BreedCommitment<T::Hash, T::BlockNumber, BalanceOf<T>>) -> T::BlockNumber {
+        commitment.committed_at.saturating_add(T::RevealDelay::get())
+    }
+
+    fn deposit_value() -> BalanceOf<T> {
+        BalanceOf::<T>::from(T::DepositValue::get())
+    }
+
+    /// Hand an auctioned kitty to the highest bidder, or back to the seller if there is none
+    fn settle_auction(kitty_id: T::KittyIndex) {
+        let auction = match <Auctions<T>>::take(kitty_id) {
+            Some(auction) => auction,
+            None => return,
+        };
+        let (winner, price) = match auction.best_bid {
+            Some(best_bid) => best_bid,
+            None => {
+                Self::deposit_event(RawEvent::AuctionCancelled(auction.seller, kitty_id));
+                return;
+            }
+        };
+
+        // The reserved deposit becomes free again here and is reserved anew by `do_transfer`
+        T::Currency::unreserve(&winner, price.saturating_add(Self::deposit_value()));
+        let result = with_transaction(|| {
+            let result = T::Currency::transfer(&winner, &auction.seller, price, ExistenceRequirement::AllowDeath)
+                .and_then(|_| Self::do_transfer(&auction.seller, &winner, kitty_id));
+            match result {
+                Ok(()) => TransactionOutcome::Commit(Ok(())),
+                Err(e) => TransactionOutcome::Rollback(Err(e)),
+            }
+        });
+
+        match result {
+            Ok(()) => Self::deposit_event(RawEvent::AuctionSettled(auction.seller, winner, kitty_id, price)),
+            Err(_) => Self::deposit_event(RawEvent::AuctionCancelled(auction.seller, kitty_id)),
+        }
+    }
+
     fn next_kitty_id() -> sp_std::result::Result<T::KittyIndex, DispatchError> {
         let kitty_id = Self::kitties_count();
         if kitty_id == T::KittyIndex::max_value() {
@@ -151,91 +733,168 @@ impl<T: Trait> Module<T> {
     }
 
     fn add_kitty_to_owner(owner: &T::AccountId, kitty_id: T::KittyIndex) -> DispatchResult {
-        T::Currency::reserve(&owner, BalanceOf::<T>::from(T::DepositValue::get()))?;
-        let mut kitty_list = <OwnedKitties<T>>::get(&owner);
-        kitty_list.push(kitty_id);
-        <OwnedKitties<T>>::insert(&owner, kitty_list);
+        T::Currency::reserve(&owner, Self::deposit_value())?;
+        <OwnedKitties<T>>::insert(owner, kitty_id, ());
+        <OwnedKittiesCount<T>>::mutate(owner, |count| *count = count.saturating_add(1));
+
+        Ok(())
+    }
+
+    /// Move a kitty and its deposit to a new owner, withdrawing its approval and its sale and sire offers
+    fn do_transfer(from: &T::AccountId, to: &T::AccountId, kitty_id: T::KittyIndex) -> DispatchResult {
+        // Reserve from the receiver first so a failure leaves the kitty with its owner
+        Self::add_kitty_to_owner(to, kitty_id)?;
+        Self::remove_kitty_from_owner(from, kitty_id)?;
+        <KittyOwner<T>>::insert(kitty_id, to.clone());
+        <KittyApprovals<T>>::remove(kitty_id);
+        <KittyPrices<T>>::remove(kitty_id);
+        <SireOffers<T>>::remove(kitty_id);
 
         Ok(())
     }
 
     fn remove_kitty_from_owner(owner: &T::AccountId, kitty_id: T::KittyIndex) -> DispatchResult {
-        let mut kitty_list = <OwnedKitties<T>>::get(&owner);
-        if let Some(index) = kitty_list.iter().position(|x| *x == kitty_id) {
-            T::Currency::unreserve(&owner, BalanceOf::<T>::from(T::DepositValue::get()));
-            kitty_list.remove(index);
-            <OwnedKitties<T>>::insert(owner, kitty_list);
+        if <OwnedKitties<T>>::contains_key(owner, kitty_id) {
+            T::Currency::unreserve(&owner, Self::deposit_value());
+            <OwnedKitties<T>>::remove(owner, kitty_id);
+            <OwnedKittiesCount<T>>::mutate_exists(owner, |count| {
+                *count = count.map(|count| count.saturating_sub(1)).filter(|count| *count > 0);
+            });
         }
         Ok(())
     }
 
-    fn do_breed(sender: T::AccountId, kitty_id_1: T::KittyIndex, kitty_id_2: T::KittyIndex) -> sp_std::result::Result<T::KittyIndex, DispatchError> {
-        let kitty1 = Self::kitties(kitty_id_1).ok_or(Error::<T>::InvalidKittyId)?;
-        let kitty2 = Self::kitties(kitty_id_2).ok_or(Error::<T>::InvalidKittyId)?;
-
-        ensure!(kitty_id_1 != kitty_id_2, Error::<T>::RequireDifferentParent);
-
-        let kitty_id = Self::next_kitty_id()?;
-
-        let kitty1_dna = kitty1.0;
-        let kitty2_dna = kitty2.0;
+    /// The number of partners and children of a kitty, which bounds the work of releasing it
+    pub fn relatives_count(kitty_id: T::KittyIndex) -> u32 {
+        Self::kitty_partners_count(kitty_id).saturating_add(Self::kitty_children_count(kitty_id))
+    }
 
-        // Generate a random 128bit value
-        let selector = Self::random_value(&sender);
-        let mut new_dna = [0u8; 16];
+    /// Remove a kitty and its records, keeping the parent links that make up its lineage
+    fn remove_kitty(kitty_id: T::KittyIndex) {
+        <Kitties<T>>::remove(kitty_id);
+        <KittyOwner<T>>::remove(kitty_id);
+        <KittyBreeding<T>>::remove(kitty_id);
+        <KittyApprovals<T>>::remove(kitty_id);
+        <KittyPrices<T>>::remove(kitty_id);
+        <SireOffers<T>>::remove(kitty_id);
 
-        // Combine parents and selector to create new kitty
-        for i in 0..kitty1_dna.len() {
-            new_dna[i] = combine_dna(kitty1_dna[i], kitty2_dna[i], selector[i]);
+        if let Some((parent_1, parent_2)) = Self::kitty_parents(kitty_id) {
+            Self::remove_child(parent_1, kitty_id);
+            Self::remove_child(parent_2, kitty_id);
         }
+        for partner in Self::kitty_partners(kitty_id) {
+            Self::remove_partner(partner, kitty_id);
+        }
+        <KittyPartners<T>>::remove_prefix(kitty_id);
+        <KittyPartnersCount<T>>::remove(kitty_id);
 
-        Self::insert_kitty(&sender, kitty_id, Kitty(new_dna))?;
+        <KittyChildren<T>>::remove_prefix(kitty_id);
+        <KittyChildrenCount<T>>::remove(kitty_id);
+    }
 
+    /// Record `child` as born to `parent_1` and `parent_2`
+    fn record_parents(child: T::KittyIndex, parent_1: T::KittyIndex, parent_2: T::KittyIndex) {
         // parents
         // time: O(1); space: O(1)
-        <KittyParents<T>>::insert(kitty_id, (kitty_id_1, kitty_id_2));
+        <KittyParents<T>>::insert(child, (parent_1, parent_2));
 
         // partners
-        // time: O(n); space: O(n)
-        let mut partner_list = <KittyPartners<T>>::get(&kitty_id_1);
-        let mut prev_partner = false;
-        for item in &partner_list {
-            if *item == kitty_id_2 {
-                prev_partner = true;
-                break;
-            }
-        }
-        if prev_partner == false {
-            partner_list.push(kitty_id_2);
-            <KittyPartners<T>>::insert(kitty_id_1, partner_list);
-            partner_list = <KittyPartners<T>>::get(&kitty_id_2);
-            partner_list.push(kitty_id_1);
-            <KittyPartners<T>>::insert(kitty_id_2, partner_list);
-        }
-
-        // siblings: ignore the potential duplicate kitties added to sibling list here
-        // time: O(n); space: O(n)
-        let mut new_sibling_list = <KittySiblings<T>>::get(&kitty_id);
-        let mut child_list = <KittyChildren<T>>::get(&kitty_id_1);
-        for item in &child_list {
-            let mut sibling_list = <KittySiblings<T>>::get(item);
-            sibling_list.push(kitty_id);
-            <KittyPartners<T>>::insert(item, sibling_list);
-            new_sibling_list.push(*item);
-        }
-        child_list = <KittyChildren<T>>::get(&kitty_id_2);
-        for item in &child_list {
-            let mut sibling_list = <KittySiblings<T>>::get(item);
-            sibling_list.push(kitty_id);
-            <KittyPartners<T>>::insert(item, sibling_list);
-            new_sibling_list.push(*item);
-        }
-        <KittySiblings<T>>::insert(kitty_id, new_sibling_list);
+        // time: O(1); space: O(1)
+        Self::add_partner(parent_1, parent_2);
+        Self::add_partner(parent_2, parent_1);
 
         // children
-        // time: O(n); space: O(n)
-        <KittyChildren<T>>::mutate(&kitty_id_1, |val| val.push(kitty_id));
-        <KittyChildren<T>>::mutate(&kitty_id_2, |val| val.push(kitty_id));
+        // time: O(1); space: O(1)
+        Self::add_child(parent_1, child);
+        Self::add_child(parent_2, child);
+    }
+
+    /// Record `partner` as bred with `kitty_id`, if it was not already
+    fn add_partner(kitty_id: T::KittyIndex, partner: T::KittyIndex) {
+        if !<KittyPartners<T>>::contains_key(kitty_id, partner) {
+            <KittyPartners<T>>::insert(kitty_id, partner, ());
+            <KittyPartnersCount<T>>::mutate(kitty_id, |count| *count = count.saturating_add(1));
+        }
+    }
+
+    fn remove_partner(kitty_id: T::KittyIndex, partner: T::KittyIndex) {
+        if <KittyPartners<T>>::contains_key(kitty_id, partner) {
+            <KittyPartners<T>>::remove(kitty_id, partner);
+            <KittyPartnersCount<T>>::mutate_exists(kitty_id, |count| {
+                *count = count.map(|count| count.saturating_sub(1)).filter(|count| *count > 0);
+            });
+        }
+    }
+
+    /// Record `child` as born to `parent`
+    fn add_child(parent: T::KittyIndex, child: T::KittyIndex) {
+        <KittyChildren<T>>::insert(parent, child, ());
+        <KittyChildrenCount<T>>::mutate(parent, |count| *count = count.saturating_add(1));
+    }
+
+    fn remove_child(parent: T::KittyIndex, child: T::KittyIndex) {
+        if <KittyChildren<T>>::contains_key(parent, child) {
+            <KittyChildren<T>>::remove(parent, child);
+            <KittyChildrenCount<T>>::mutate_exists(parent, |count| {
+                *count = count.map(|count| count.saturating_sub(1)).filter(|count| *count > 0);
+            });
+        }
+    }
+
+    /// Breed a kitty of `sender` with its own or an offered kitty, paying the sire fee
+    fn breed_with(
+        sender: T::AccountId,
+        kitty_id_1: T::KittyIndex,
+        kitty_id_2: T::KittyIndex,
+        max_sire_fee: BalanceOf<T>,
+        seed: [u8; 16],
+    ) -> sp_std::result::Result<T::KittyIndex, DispatchError> {
+        let owner_1 = Self::kitty_owner(kitty_id_1).ok_or(Error::<T>::InvalidKittyId)?;
+        ensure!(sender == owner_1, Error::<T>::NotValidOwner);
+        let owner_2 = Self::kitty_owner(kitty_id_2).ok_or(Error::<T>::InvalidKittyId)?;
+
+        if sender != owner_2 {
+            let fee = Self::sire_offer(kitty_id_2).ok_or(Error::<T>::NoSireOffer)?;
+            ensure!(fee <= max_sire_fee, Error::<T>::PriceTooHigh);
+            T::Currency::transfer(&sender, &owner_2, fee, ExistenceRequirement::KeepAlive)?;
+            Self::deposit_event(RawEvent::SireFeePaid(sender.clone(), owner_2, kitty_id_2, fee));
+        }
+
+        Self::do_breed(sender, kitty_id_1, kitty_id_2, seed)
+    }
+
+    fn do_breed(
+        sender: T::AccountId,
+        kitty_id_1: T::KittyIndex,
+        kitty_id_2: T::KittyIndex,
+        seed: [u8; 16],
+    ) -> sp_std::result::Result<T::KittyIndex, DispatchError> {
+        let kitty1 = Self::kitties(kitty_id_1).ok_or(Error::<T>::InvalidKittyId)?;
+        let kitty2 = Self::kitties(kitty_id_2).ok_or(Error::<T>::InvalidKittyId)?;
+
+        ensure!(kitty_id_1 != kitty_id_2, Error::<T>::RequireDifferentParent);
+
+        let now = <frame_system::Module<T>>::block_number();
+        let mut breeding1 = Self::kitty_breeding(kitty_id_1);
+        let mut breeding2 = Self::kitty_breeding(kitty_id_2);
+        ensure!(breeding1.cooldown_until <= now && breeding2.cooldown_until <= now, Error::<T>::KittyOnCooldown);
+
+        let kitty_id = Self::next_kitty_id()?;
+
+        // Inherit the genes of both parents to create new kitty
+        let new_dna = genome::inherit(&kitty1.0, &kitty2.0, &seed);
+
+        Self::insert_kitty(&sender, kitty_id, Kitty(new_dna))?;
+
+        // generation and cooldowns
+        let generation = breeding1.generation.max(breeding2.generation).saturating_add(1);
+        <KittyBreeding<T>>::insert(kitty_id, BreedingInfo { generation, cooldown_until: now });
+        breeding1.cooldown_until = now.saturating_add(Self::cooldown(breeding1.generation));
+        breeding2.cooldown_until = now.saturating_add(Self::cooldown(breeding2.generation));
+        <KittyBreeding<T>>::insert(kitty_id_1, breeding1);
+        <KittyBreeding<T>>::insert(kitty_id_2, breeding2);
+
+        Self::record_parents(kitty_id, kitty_id_1, kitty_id_2);
 
         Self::deposit_event(RawEvent::Breeded(sender, kitty_id_1, kitty_id_2, kitty_id));
 
@@ -254,9 +913,12 @@ mod tests {
     use sp_runtime::{
         traits::{BlakeTwo256, IdentityLookup}, testing::Header, Perbill,
     };
-    use frame_support::{assert_noop};
+    use frame_support::{assert_noop, assert_ok};
     use frame_system as system;
 
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+
     pub(crate) type Balance = u128;
 
     impl_outer_origin! {
@@ -329,10 +991,34 @@ mod tests {
 	type KittyIndex = u32;
 	type Currency = Balances;
 	type DepositValue = DepositValue;
+	type MaxAuctionsPerBlock = MaxAuctionsPerBlock;
+	type BaseCooldown = BaseCooldown;
+	type MaxCooldown = MaxCooldown;
+	type CommitRevealBreeding = CommitRevealBreeding;
+	type RevealDelay = RevealDelay;
+	type CommitDeposit = CommitDeposit;
+	type TokenUriBase = TokenUriBase;
+    }
+
+    thread_local! {
+        static COMMIT_REVEAL_BREEDING: RefCell<bool> = RefCell::new(false);
+    }
+
+    pub struct CommitRevealBreeding;
+    impl Get<bool> for CommitRevealBreeding {
+        fn get() -> bool {
+            COMMIT_REVEAL_BREEDING.with(|v| *v.borrow())
+        }
     }
 
     parameter_types! {
 	pub const DepositValue: u32 = 10;
+	pub const MaxAuctionsPerBlock: u32 = 2;
+	pub const BaseCooldown: u64 = 5;
+	pub const MaxCooldown: u64 = 12;
+	pub const RevealDelay: u64 = 2;
+	pub const CommitDeposit: u32 = 5;
+	pub TokenUriBase: Vec<u8> = b"kitty/".to_vec();
     }
 
     pub type Kitties = Module<Test>;
@@ -348,18 +1034,59 @@ mod tests {
     }
 
     pub fn new_test_ext() -> sp_io::TestExternalities {
+        new_test_ext_with_kitties(vec![])
+    }
+
+    pub fn new_test_ext_with_kitties(kitties: Vec<(u64, [u8; 16], Option<(u32, u32)>)>) -> sp_io::TestExternalities {
         let mut storage = frame_system::GenesisConfig::default()
             .build_storage::<Test>()
             .unwrap();
         let _ = pallet_balances::GenesisConfig::<Test> {
-            balances: vec![(1, 500), (2, 500)],
+            balances: vec![(1, 500), (2, 500), (3, 500)],
         }
         .assimilate_storage(&mut storage);
+        let _ = crate::GenesisConfig::<Test> { kitties }.assimilate_storage(&mut storage);
 
         let ext = sp_io::TestExternalities::from(storage);
         ext
     }
 
+    #[test]
+    fn genesis_kitties_work() {
+        new_test_ext_with_kitties(vec![
+            (1, [1; 16], None),
+            (1, [2; 16], None),
+            (2, [3; 16], Some((0, 1))),
+        ]).execute_with(|| {
+            assert_eq!(Kitties::kitties_count(), 3);
+            assert_eq!(Kitties::kitties(2).map(|kitty| kitty.0), Some([3; 16]));
+            assert_eq!(Kitties::kitty_owner(2), Some(2));
+            assert_eq!(Kitties::owned_kitties_count(1), 2);
+            assert_eq!(Kitties::owned_kitties(&2), vec![2]);
+            assert_eq!(Balances::reserved_balance(1), 20);
+            assert_eq!(Balances::reserved_balance(2), 10);
+
+            assert_eq!(Kitties::kitty_parents(2), Some((0, 1)));
+            assert_eq!(Kitties::kitty_children(0), vec![2]);
+            assert_eq!(Kitties::kitty_partners(1), vec![0]);
+            assert_eq!(Kitties::kitty_breeding(2).generation, 1);
+
+            // Minting continues after the genesis kitties
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(3)));
+            assert_eq!(Kitties::kitty_owner(3), Some(3));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "genesis kitty parents must come first")]
+    fn genesis_kitties_fail_unborn_parent() {
+        new_test_ext_with_kitties(vec![
+            (1, [1; 16], None),
+            (1, [2; 16], Some((0, 2))),
+        ]);
+    }
+
     #[test]
     fn kitty_create_works() {
         new_test_ext().execute_with(|| {
@@ -419,7 +1146,7 @@ mod tests {
             run_to_block(10);
             assert_eq!(Kitties::create(Origin::signed(1)), Ok(()));
             assert_eq!(Kitties::create(Origin::signed(1)), Ok(()));
-            assert_eq!(Kitties::breed(Origin::signed(1), 0, 1), Ok(()));
+            assert_eq!(Kitties::breed(Origin::signed(1), 0, 1, 0), Ok(()));
         });
     }
 
@@ -429,7 +1156,7 @@ mod tests {
             run_to_block(10);
             assert_eq!(Kitties::create(Origin::signed(1)), Ok(()));
             assert_noop!(
-                Kitties::breed(Origin::signed(1), 3, 0),
+                Kitties::breed(Origin::signed(1), 3, 0, 0),
                 Error::<Test>::InvalidKittyId
             );
         });
@@ -441,9 +1168,972 @@ mod tests {
             run_to_block(10);
             assert_eq!(Kitties::create(Origin::signed(1)), Ok(()));
             assert_noop!(
-                Kitties::breed(Origin::signed(1), 0, 0),
+                Kitties::breed(Origin::signed(1), 0, 0, 0),
                 Error::<Test>::RequireDifferentParent
             );
         });
     }
+
+    #[test]
+    fn kitty_breed_records_relationships() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 1, 0));
+            run_to_block(15);
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 2, 0));
+
+            let sorted = |mut ids: Vec<u32>| { ids.sort(); ids };
+            assert_eq!(sorted(Kitties::kitty_children(0)), vec![3, 4]);
+            assert_eq!(Kitties::kitty_children_count(0), 2);
+            assert_eq!(Kitties::kitty_children(1), vec![3]);
+            assert_eq!(sorted(Kitties::kitty_partners(0)), vec![1, 2]);
+            assert_eq!(Kitties::kitty_partners(2), vec![0]);
+            assert_eq!(Kitties::kitty_siblings(3), vec![4]);
+            assert_eq!(Kitties::kitty_siblings(4), vec![3]);
+            assert_eq!(Kitties::kitty_siblings(0), Vec::<u32>::new());
+        });
+    }
+
+    #[test]
+    fn kitty_siblings_are_listed_once() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 1, 0));
+            run_to_block(20);
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 1, 0));
+            run_to_block(30);
+            assert_ok!(Kitties::breed(Origin::signed(1), 1, 2, 0));
+
+            // 3 and 4 share both parents, 5 shares one with each of them
+            assert_eq!(Kitties::kitty_siblings(3), vec![4, 5]);
+            assert_eq!(Kitties::kitty_siblings(4), vec![3, 5]);
+            assert_eq!(Kitties::kitty_siblings(5), vec![3, 4]);
+        });
+    }
+
+    #[test]
+    fn kitty_family_trees_work() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 1, 0));
+            run_to_block(20);
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 2, 0));
+
+            let member = |kitty_id, generation, relative| genealogy::FamilyMember { kitty_id, generation, relative };
+            let ancestors = Kitties::ancestors(3, 2).unwrap();
+            assert_eq!(ancestors.members, vec![
+                member(3, 0, None),
+                member(0, 1, Some(0)),
+                member(2, 1, Some(0)),
+                member(0, 2, Some(2)),
+                member(1, 2, Some(2)),
+            ]);
+            assert!(!ancestors.truncated);
+            assert_eq!(Kitties::ancestors(3, 1).unwrap().members.len(), 3);
+
+            let descendants = Kitties::descendants(0, 5).unwrap();
+            assert_eq!(descendants.members, vec![
+                member(0, 0, None),
+                member(2, 1, Some(0)),
+                member(3, 1, Some(0)),
+                member(3, 2, Some(1)),
+            ]);
+            assert_eq!(Kitties::descendants(4, 1), None);
+        });
+    }
+
+    #[test]
+    fn kitty_inbreeding_coefficient_works() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 1, 0));
+            run_to_block(20);
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 1, 0));
+            run_to_block(30);
+            // Parent and offspring, then full siblings
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 2, 0));
+            run_to_block(40);
+            assert_ok!(Kitties::breed(Origin::signed(1), 2, 3, 0));
+
+            assert_eq!(Kitties::inbreeding_coefficient(2, 5), Some(Perbill::zero()));
+            assert_eq!(Kitties::inbreeding_coefficient(4, 5), Some(Perbill::from_percent(25)));
+            assert_eq!(Kitties::inbreeding_coefficient(5, 5), Some(Perbill::from_percent(25)));
+            // Without the grandparents the parents look unrelated
+            assert_eq!(Kitties::inbreeding_coefficient(5, 1), Some(Perbill::zero()));
+            assert_eq!(Kitties::inbreeding_coefficient(6, 5), None);
+        });
+    }
+
+    #[test]
+    fn kitty_release_keeps_inbreeding_coefficient() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 1, 0));
+            run_to_block(20);
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 1, 0));
+            run_to_block(30);
+            // Full siblings
+            assert_ok!(Kitties::breed(Origin::signed(1), 2, 3, 0));
+            assert_eq!(Kitties::inbreeding_coefficient(4, 5), Some(Perbill::from_percent(25)));
+            assert_eq!(Kitties::ancestors(4, 2).map(|tree| tree.members.len()), Some(7));
+
+            // Releasing a parent and a grandparent hides neither the lineage nor the inbreeding
+            assert_ok!(Kitties::release(Origin::signed(1), 2, 2));
+            assert_ok!(Kitties::release(Origin::signed(1), 0, 2));
+            assert_eq!(Kitties::kitty_parents(4), Some((2, 3)));
+            assert_eq!(Kitties::kitty_breeding(4).generation, 2);
+            assert_eq!(Kitties::inbreeding_coefficient(4, 5), Some(Perbill::from_percent(25)));
+            assert_eq!(Kitties::ancestors(4, 2).map(|tree| tree.members.len()), Some(3));
+        });
+    }
+
+    #[test]
+    fn kitty_ownership_is_counted() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_eq!(Kitties::owned_kitties_count(1), 2);
+
+            assert_ok!(Kitties::transfer(Origin::signed(1), 2, 0));
+            assert_eq!(Kitties::owned_kitties(&1), vec![1]);
+            assert_eq!(Kitties::owned_kitties_count(1), 1);
+            assert_eq!(Kitties::owned_kitties_count(2), 1);
+
+            assert_ok!(Kitties::transfer(Origin::signed(1), 2, 1));
+            assert!(!OwnedKittiesCount::<Test>::contains_key(1));
+            assert_eq!(Kitties::owned_kitties_count(2), 2);
+        });
+    }
+
+    #[test]
+    fn kitty_release_works() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 1, 0));
+            run_to_block(20);
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 1, 0));
+            assert_ok!(Kitties::set_price(Origin::signed(1), 2, 100));
+            assert_eq!(Balances::free_balance(1), 500 - 40);
+
+            assert_eq!(Kitties::relatives_count(2), 0);
+            assert_ok!(Kitties::release(Origin::signed(1), 2, 0));
+            assert_eq!(Balances::free_balance(1), 500 - 30);
+            assert!(Kitties::kitties(2).is_none());
+            assert_eq!(Kitties::kitty_owner(2), None);
+            assert_eq!(Kitties::kitty_price(2), None);
+            assert_eq!(Kitties::kitty_parents(2), Some((0, 1)));
+            assert_eq!(Kitties::owned_kitties_count(1), 3);
+            assert_eq!(Kitties::kitty_children(0), vec![3]);
+            assert_eq!(Kitties::kitty_children_count(0), 1);
+            assert_eq!(Kitties::kitty_siblings(3), Vec::<u32>::new());
+
+            // A parent leaves its partners, and its children keep it as a parent
+            assert_eq!(Kitties::relatives_count(0), 2);
+            assert_noop!(
+                Kitties::release(Origin::signed(1), 0, 1),
+                Error::<Test>::TooManyRelatives
+            );
+            assert_ok!(Kitties::release(Origin::signed(1), 0, 2));
+            assert_eq!(Kitties::kitty_partners(1), Vec::<u32>::new());
+            assert_eq!(Kitties::kitty_partners_count(1), 0);
+            assert_eq!(Kitties::kitty_children_count(0), 0);
+            assert_eq!(Kitties::kitty_parents(3), Some((0, 1)));
+            assert_eq!(Kitties::kitty_children(1), vec![3]);
+            assert_eq!(Kitties::kitty_children_count(1), 1);
+            // Family trees leave the released parent out
+            assert_eq!(Kitties::ancestors(3, 1).map(|tree| tree.members.len()), Some(2));
+            let mut owned = Kitties::owned_kitties(&1);
+            owned.sort();
+            assert_eq!(owned, vec![1, 3]);
+            assert_eq!(Balances::free_balance(1), 500 - 20);
+        });
+    }
+
+    #[test]
+    fn kitty_release_fails() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_noop!(
+                Kitties::release(Origin::signed(2), 0, 0),
+                Error::<Test>::NotValidOwner
+            );
+            assert_noop!(
+                Kitties::release(Origin::signed(1), 1, 0),
+                Error::<Test>::InvalidKittyId
+            );
+            assert_ok!(Kitties::create_auction(Origin::signed(1), 0, 50, 20));
+            assert_noop!(
+                Kitties::release(Origin::signed(1), 0, 0),
+                Error::<Test>::KittyInAuction
+            );
+        });
+    }
+
+    /// Move every token of `from` to `to` through the generic interface
+    fn transfer_all<N: nft::NonFungibleAsset<u64>>(from: u64, to: u64, tokens: &[N::TokenId]) -> DispatchResult {
+        for token in tokens {
+            if N::owner_of(token) == Some(from) {
+                N::transfer(&from, &to, token)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the behaviour every collection shares, given two tokens of account 1 and one that does not exist
+    fn check_nft_collection<N: nft::NonFungibleAsset<u64>>(tokens: &[N::TokenId; 2], missing: &N::TokenId) {
+        assert_eq!(N::balance_of(&1), 2);
+        assert_eq!(N::owner_of(&tokens[0]), Some(1));
+        assert_eq!(N::owner_of(missing), None);
+        assert!(N::token_uri(&tokens[0]).is_some());
+        assert_eq!(N::token_uri(missing), None);
+
+        assert!(N::approve(&2, &tokens[0], Some(3)).is_err());
+        assert!(N::approve(&1, &tokens[0], Some(1)).is_err());
+        assert!(N::approve(&1, missing, Some(3)).is_err());
+        assert_ok!(N::approve(&1, &tokens[0], Some(3)));
+        assert_eq!(N::approved(&tokens[0]), Some(3));
+        assert_ok!(N::approve(&1, &tokens[1], Some(3)));
+        assert_ok!(N::approve(&1, &tokens[1], None));
+        assert_eq!(N::approved(&tokens[1]), None);
+
+        assert_ok!(transfer_all::<N>(1, 2, tokens));
+        assert_eq!(N::owner_of(&tokens[0]), Some(2));
+        assert_eq!(N::owner_of(&tokens[1]), Some(2));
+        assert_eq!(N::balance_of(&1), 0);
+        assert_eq!(N::balance_of(&2), 2);
+        assert_eq!(N::approved(&tokens[0]), None);
+
+        assert!(N::transfer(&1, &3, &tokens[0]).is_err());
+        assert!(N::transfer(&2, &2, &tokens[0]).is_err());
+        assert!(N::transfer(&1, &2, missing).is_err());
+    }
+
+    #[test]
+    fn kitty_nft_works() {
+        use nft::NonFungibleAsset;
+
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_eq!(<Kitties as NonFungibleAsset<u64>>::token_uri(&1), Some(b"kitty/1".to_vec()));
+            assert_noop!(
+                <Kitties as NonFungibleAsset<u64>>::approve(&2, &0, Some(3)),
+                Error::<Test>::NotValidOwner
+            );
+
+            check_nft_collection::<Kitties>(&[0, 1], &2);
+            assert_eq!(Kitties::owned_kitties_count(2), 2);
+            assert_noop!(
+                <Kitties as NonFungibleAsset<u64>>::transfer(&1, &3, &0),
+                Error::<Test>::NotValidOwner
+            );
+        });
+    }
+
+    thread_local! {
+        static BASIC_OWNERS: RefCell<BTreeMap<u32, u64>> = RefCell::new(BTreeMap::new());
+        static BASIC_APPROVALS: RefCell<BTreeMap<u32, u64>> = RefCell::new(BTreeMap::new());
+    }
+
+    /// A plain collection of numbered tokens, with no genome or breeding, to check that the
+    /// interface is not tied to kitties
+    pub struct BasicCollection;
+
+    impl BasicCollection {
+        fn mint(owner: u64, token_id: u32) -> DispatchResult {
+            BASIC_OWNERS.with(|owners| -> DispatchResult {
+                let mut owners = owners.borrow_mut();
+                ensure!(!owners.contains_key(&token_id), DispatchError::Other("token already exists"));
+                owners.insert(token_id, owner);
+                Ok(())
+            })
+        }
+    }
+
+    impl nft::NonFungibleAsset<u64> for BasicCollection {
+        type TokenId = u32;
+
+        fn owner_of(token_id: &u32) -> Option<u64> {
+            BASIC_OWNERS.with(|owners| owners.borrow().get(token_id).copied())
+        }
+
+        fn balance_of(who: &u64) -> u32 {
+            BASIC_OWNERS.with(|owners| owners.borrow().values().filter(|owner| *owner == who).count() as u32)
+        }
+
+        fn transfer(from: &u64, to: &u64, token_id: &u32) -> DispatchResult {
+            let owner = Self::owner_of(token_id).ok_or(DispatchError::Other("no such token"))?;
+            ensure!(*from == owner, DispatchError::Other("not the token owner"));
+            ensure!(from != to, DispatchError::Other("cannot transfer a token to its owner"));
+
+            BASIC_OWNERS.with(|owners| owners.borrow_mut().insert(*token_id, *to));
+            BASIC_APPROVALS.with(|approvals| approvals.borrow_mut().remove(token_id));
+            Ok(())
+        }
+
+        fn approve(owner: &u64, token_id: &u32, spender: Option<u64>) -> DispatchResult {
+            let token_owner = Self::owner_of(token_id).ok_or(DispatchError::Other("no such token"))?;
+            ensure!(*owner == token_owner, DispatchError::Other("not the token owner"));
+            ensure!(spender != Some(*owner), DispatchError::Other("cannot approve the token owner"));
+
+            BASIC_APPROVALS.with(|approvals| match spender {
+                Some(spender) => approvals.borrow_mut().insert(*token_id, spender),
+                None => approvals.borrow_mut().remove(token_id),
+            });
+            Ok(())
+        }
+
+        fn approved(token_id: &u32) -> Option<u64> {
+            BASIC_APPROVALS.with(|approvals| approvals.borrow().get(token_id).copied())
+        }
+
+        fn token_uri(token_id: &u32) -> Option<Vec<u8>> {
+            Self::owner_of(token_id).map(|_| format!("token/{}", token_id).into_bytes())
+        }
+    }
+
+    #[test]
+    fn basic_nft_collection_works() {
+        use nft::NonFungibleAsset;
+
+        new_test_ext().execute_with(|| {
+            assert_ok!(BasicCollection::mint(1, 0));
+            assert_ok!(BasicCollection::mint(1, 1));
+            assert!(BasicCollection::mint(2, 1).is_err());
+            assert_eq!(BasicCollection::token_uri(&1), Some(b"token/1".to_vec()));
+
+            check_nft_collection::<BasicCollection>(&[0, 1], &2);
+            // The collection keeps to its own tokens
+            assert_eq!(Kitties::kitty_owner(0), None);
+        });
+    }
+
+    #[test]
+    fn kitty_transfer_from_approved_works() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_noop!(
+                Kitties::transfer_from(Origin::signed(3), 1, 2, 0),
+                Error::<Test>::NotApproved
+            );
+            assert_noop!(
+                Kitties::approve(Origin::signed(3), 0, Some(3)),
+                Error::<Test>::NotValidOwner
+            );
+
+            assert_ok!(Kitties::approve(Origin::signed(1), 0, Some(3)));
+            assert_eq!(Kitties::kitty_approval(0), Some(3));
+            assert_noop!(
+                Kitties::transfer_from(Origin::signed(3), 2, 3, 0),
+                Error::<Test>::NotValidOwner
+            );
+            assert_ok!(Kitties::transfer_from(Origin::signed(3), 1, 2, 0));
+            assert_eq!(Kitties::kitty_owner(0), Some(2));
+
+            // The approval does not survive the transfer
+            assert_eq!(Kitties::kitty_approval(0), None);
+            assert_noop!(
+                Kitties::transfer_from(Origin::signed(3), 2, 1, 0),
+                Error::<Test>::NotApproved
+            );
+        });
+    }
+
+    #[test]
+    fn kitty_approve_can_be_cleared() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::approve(Origin::signed(1), 0, Some(3)));
+            assert_ok!(Kitties::approve(Origin::signed(1), 0, None));
+            assert_noop!(
+                Kitties::transfer_from(Origin::signed(3), 1, 2, 0),
+                Error::<Test>::NotApproved
+            );
+        });
+    }
+
+    #[test]
+    fn kitty_transfer_from_operator_works() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_noop!(
+                Kitties::set_approval_for_all(Origin::signed(1), 1, true),
+                Error::<Test>::NotValidReceiver
+            );
+            assert_ok!(Kitties::set_approval_for_all(Origin::signed(1), 3, true));
+            assert!(Kitties::is_operator(1, 3));
+
+            // Operators may approve others and transfer themselves
+            assert_ok!(Kitties::approve(Origin::signed(3), 0, Some(2)));
+            assert_ok!(Kitties::transfer_from(Origin::signed(2), 1, 2, 0));
+            assert_ok!(Kitties::transfer_from(Origin::signed(3), 1, 3, 1));
+            assert_eq!(Kitties::kitty_owner(1), Some(3));
+
+            // Operators act for an owner, not for a kitty
+            assert_noop!(
+                Kitties::transfer_from(Origin::signed(3), 2, 3, 0),
+                Error::<Test>::NotApproved
+            );
+
+            assert_ok!(Kitties::set_approval_for_all(Origin::signed(1), 3, false));
+            assert!(!Kitties::is_operator(1, 3));
+        });
+    }
+
+    /// Run to block `n`, giving every block a distinct hash
+    fn run_to_block_with_hashes(n: u64) {
+        while System::block_number() < n {
+            let now = System::block_number();
+            frame_system::BlockHash::<Test>::insert(now, H256::repeat_byte(now as u8));
+            run_to_block(now + 1);
+        }
+    }
+
+    #[test]
+    fn kitty_commit_reveal_breed_works() {
+        COMMIT_REVEAL_BREEDING.with(|v| *v.borrow_mut() = true);
+        new_test_ext().execute_with(|| {
+            run_to_block_with_hashes(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_noop!(
+                Kitties::breed(Origin::signed(1), 0, 1, 0),
+                Error::<Test>::CommitRevealRequired
+            );
+
+            let salt = [7u8; 32];
+            assert_noop!(
+                Kitties::reveal_breed(Origin::signed(1), 0, 1, 0, salt),
+                Error::<Test>::NoBreedCommitment
+            );
+            let hash = BlakeTwo256::hash_of(&(0u32, 1u32, 0 as Balance, salt));
+            assert_ok!(Kitties::commit_breed(Origin::signed(1), hash));
+            assert_eq!(Kitties::breed_commitment(1), Some(BreedCommitment { hash, committed_at: 10, deposit: 5 }));
+            assert_eq!(Balances::reserved_balance(1), 20 + 5);
+            assert_noop!(
+                Kitties::commit_breed(Origin::signed(1), hash),
+                Error::<Test>::BreedCommitmentPending
+            );
+
+            // The seed is the hash of block 12, known once block 13 starts
+            run_to_block_with_hashes(12);
+            assert_noop!(
+                Kitties::reveal_breed(Origin::signed(1), 0, 1, 0, salt),
+                Error::<Test>::RevealTooEarly
+            );
+            run_to_block_with_hashes(13);
+            assert_noop!(
+                Kitties::reveal_breed(Origin::signed(1), 1, 0, 0, salt),
+                Error::<Test>::InvalidReveal
+            );
+            assert_ok!(Kitties::reveal_breed(Origin::signed(1), 0, 1, 0, salt));
+            assert_eq!(Kitties::kitty_parents(2), Some((0, 1)));
+            assert_eq!(Kitties::breed_commitment(1), None);
+            // The commitment deposit is returned, the new kitty's deposit is reserved
+            assert_eq!(Balances::reserved_balance(1), 30);
+            assert_eq!(Balances::free_balance(1), 500 - 30);
+
+            let seed = (H256::repeat_byte(12), salt).using_encoded(blake2_128);
+            let dna = genome::inherit(&Kitties::kitties(0).unwrap().0, &Kitties::kitties(1).unwrap().0, &seed);
+            assert_eq!(Kitties::kitties(2).unwrap().0, dna);
+        });
+        COMMIT_REVEAL_BREEDING.with(|v| *v.borrow_mut() = false);
+    }
+
+    #[test]
+    fn kitty_commit_reveal_breed_fails_expired() {
+        COMMIT_REVEAL_BREEDING.with(|v| *v.borrow_mut() = true);
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            let salt = [7u8; 32];
+            let hash = BlakeTwo256::hash_of(&(0u32, 1u32, 0 as Balance, salt));
+            assert_ok!(Kitties::commit_breed(Origin::signed(1), hash));
+            assert_noop!(
+                Kitties::remove_expired_commitment(Origin::signed(2), 1),
+                Error::<Test>::CommitmentNotExpired
+            );
+
+            // No hash is kept for the seed block
+            run_to_block(20);
+            assert_noop!(
+                Kitties::reveal_breed(Origin::signed(1), 0, 1, 0, salt),
+                Error::<Test>::RevealExpired
+            );
+
+            // Anyone can clear the commitment, and the breeder loses the deposit
+            assert_ok!(Kitties::remove_expired_commitment(Origin::signed(2), 1));
+            assert_eq!(Kitties::breed_commitment(1), None);
+            assert_eq!(Balances::reserved_balance(1), 20);
+            assert_eq!(Balances::free_balance(1), 500 - 20 - 5);
+            assert_noop!(
+                Kitties::remove_expired_commitment(Origin::signed(2), 1),
+                Error::<Test>::NoBreedCommitment
+            );
+            assert_ok!(Kitties::commit_breed(Origin::signed(1), hash));
+        });
+        COMMIT_REVEAL_BREEDING.with(|v| *v.borrow_mut() = false);
+    }
+
+    #[test]
+    fn kitty_commit_breed_fails_disabled() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_noop!(
+                Kitties::commit_breed(Origin::signed(1), H256::zero()),
+                Error::<Test>::CommitRevealDisabled
+            );
+        });
+    }
+
+    #[test]
+    fn kitty_set_price_works() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::set_price(Origin::signed(1), 0, 100));
+            assert_eq!(Kitties::kitty_price(0), Some(100));
+            assert_ok!(Kitties::clear_price(Origin::signed(1), 0));
+            assert_eq!(Kitties::kitty_price(0), None);
+        });
+    }
+
+    #[test]
+    fn kitty_set_price_fails_not_valid_owner() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_noop!(
+                Kitties::set_price(Origin::signed(2), 0, 100),
+                Error::<Test>::NotValidOwner
+            );
+            assert_noop!(
+                Kitties::clear_price(Origin::signed(1), 0),
+                Error::<Test>::NotForSale
+            );
+        });
+    }
+
+    #[test]
+    fn kitty_buy_works() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::set_price(Origin::signed(1), 0, 100));
+            assert_ok!(Kitties::buy(Origin::signed(2), 0, 150));
+
+            assert_eq!(Kitties::kitty_owner(0), Some(2));
+            assert_eq!(Kitties::owned_kitties(&1), Vec::<u32>::new());
+            assert_eq!(Kitties::owned_kitties(&2), vec![0]);
+            assert_eq!(Kitties::kitty_price(0), None);
+            // The seller gets the price and its deposit back, the buyer reserves a new one
+            assert_eq!(Balances::free_balance(1), 600);
+            assert_eq!(Balances::reserved_balance(1), 0);
+            assert_eq!(Balances::free_balance(2), 390);
+            assert_eq!(Balances::reserved_balance(2), 10);
+        });
+    }
+
+    #[test]
+    fn kitty_buy_fails() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_noop!(
+                Kitties::buy(Origin::signed(2), 0, 150),
+                Error::<Test>::NotForSale
+            );
+            assert_ok!(Kitties::set_price(Origin::signed(1), 0, 100));
+            assert_noop!(
+                Kitties::buy(Origin::signed(2), 0, 99),
+                Error::<Test>::PriceTooHigh
+            );
+            assert_noop!(
+                Kitties::buy(Origin::signed(1), 0, 100),
+                Error::<Test>::NotValidReceiver
+            );
+        });
+    }
+
+    #[test]
+    fn kitty_transfer_clears_price() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::set_price(Origin::signed(1), 0, 100));
+            assert_ok!(Kitties::transfer(Origin::signed(1), 2, 0));
+            assert_eq!(Kitties::kitty_price(0), None);
+        });
+    }
+
+    #[test]
+    fn kitty_auction_settles_to_highest_bidder() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::set_price(Origin::signed(1), 0, 300));
+            assert_ok!(Kitties::create_auction(Origin::signed(1), 0, 50, 12));
+            assert_eq!(Kitties::kitty_price(0), None);
+
+            assert_ok!(Kitties::bid(Origin::signed(2), 0, 50));
+            assert_eq!(Balances::reserved_balance(2), 60);
+            assert_ok!(Kitties::bid(Origin::signed(3), 0, 80));
+            // The losing bid is unreserved as soon as it is outbid
+            assert_eq!(Balances::reserved_balance(2), 0);
+            assert_eq!(Balances::reserved_balance(3), 90);
+
+            run_to_block(12);
+            assert_eq!(Kitties::kitty_owner(0), Some(1));
+            run_to_block(13);
+            assert_eq!(Kitties::kitty_owner(0), Some(3));
+            assert_eq!(Kitties::auction(0), None);
+            assert!(!<AuctionsEndingAt<Test>>::contains_key(12));
+            assert_eq!(Balances::free_balance(1), 580);
+            assert_eq!(Balances::reserved_balance(1), 0);
+            assert_eq!(Balances::free_balance(3), 410);
+            assert_eq!(Balances::reserved_balance(3), 10);
+        });
+    }
+
+    #[test]
+    fn kitty_auction_without_bids_keeps_kitty() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create_auction(Origin::signed(1), 0, 50, 12));
+            run_to_block(13);
+            assert_eq!(Kitties::kitty_owner(0), Some(1));
+            assert_eq!(Kitties::auction(0), None);
+            assert_ok!(Kitties::transfer(Origin::signed(1), 2, 0));
+        });
+    }
+
+    #[test]
+    fn kitty_auction_blocks_transfer_and_sale() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create_auction(Origin::signed(1), 0, 50, 12));
+            assert_noop!(
+                Kitties::transfer(Origin::signed(1), 2, 0),
+                Error::<Test>::KittyInAuction
+            );
+            assert_noop!(
+                Kitties::set_price(Origin::signed(1), 0, 100),
+                Error::<Test>::KittyInAuction
+            );
+            assert_noop!(
+                Kitties::create_auction(Origin::signed(1), 0, 50, 13),
+                Error::<Test>::KittyInAuction
+            );
+        });
+    }
+
+    #[test]
+    fn kitty_bid_fails() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_noop!(
+                Kitties::bid(Origin::signed(2), 0, 50),
+                Error::<Test>::NoSuchAuction
+            );
+            assert_ok!(Kitties::create_auction(Origin::signed(1), 0, 50, 12));
+            assert_noop!(
+                Kitties::bid(Origin::signed(1), 0, 50),
+                Error::<Test>::NotValidReceiver
+            );
+            assert_noop!(
+                Kitties::bid(Origin::signed(2), 0, 49),
+                Error::<Test>::BidTooLow
+            );
+            assert_ok!(Kitties::bid(Origin::signed(2), 0, 60));
+            assert_noop!(
+                Kitties::bid(Origin::signed(3), 0, 60),
+                Error::<Test>::BidTooLow
+            );
+        });
+    }
+
+    #[test]
+    fn kitty_create_auction_fails() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_noop!(
+                Kitties::create_auction(Origin::signed(2), 0, 50, 12),
+                Error::<Test>::NotValidOwner
+            );
+            assert_noop!(
+                Kitties::create_auction(Origin::signed(1), 0, 50, 10),
+                Error::<Test>::InvalidAuctionEnd
+            );
+            assert_ok!(Kitties::create_auction(Origin::signed(1), 0, 50, 12));
+            assert_ok!(Kitties::create_auction(Origin::signed(1), 1, 50, 12));
+            assert_noop!(
+                Kitties::create_auction(Origin::signed(1), 2, 50, 12),
+                Error::<Test>::TooManyAuctions
+            );
+        });
+    }
+
+    #[test]
+    fn kitty_cancel_auction_works() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create_auction(Origin::signed(1), 0, 50, 12));
+            assert_ok!(Kitties::create_auction(Origin::signed(1), 1, 50, 12));
+            assert_ok!(Kitties::cancel_auction(Origin::signed(1), 0));
+            assert_eq!(Kitties::auctions_ending_at(12), vec![1]);
+
+            assert_ok!(Kitties::bid(Origin::signed(2), 1, 50));
+            assert_noop!(
+                Kitties::cancel_auction(Origin::signed(1), 1),
+                Error::<Test>::AuctionHasBids
+            );
+        });
+    }
+
+    #[test]
+    fn genome_decoding_works() {
+        use genome::*;
+
+        let dna = [7, 2, 1, 3, 2, 0, 4, 9, 250, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(genome::decode(&dna), Genome {
+            body_colour: Allele { dominant: BodyColour::Ginger, recessive: BodyColour::Grey },
+            pattern: Allele { dominant: Pattern::Spotted, recessive: Pattern::Solid },
+            eye_shape: Allele { dominant: EyeShape::Sleepy, recessive: EyeShape::Round },
+            eye_colour: Allele { dominant: EyeColour::Odd, recessive: EyeColour::Odd },
+            rarity: Rarity::Rare,
+        });
+    }
+
+    #[test]
+    fn genome_inheritance_is_deterministic() {
+        let a = [1u8; 16];
+        let b = [2u8; 16];
+        let seed = [42u8; 16];
+        assert_eq!(genome::inherit(&a, &b, &seed), genome::inherit(&a, &b, &seed));
+    }
+
+    #[test]
+    fn genome_inheritance_orders_genes_by_dominance() {
+        // Every gene of one parent selects the most dominant variant, every gene of the other a recessive one
+        let a = [0u8; 16];
+        let b = [3u8; 16];
+        for i in 0..64u8 {
+            let child = genome::inherit(&a, &b, &[i; 16]);
+            for at in (0..8).step_by(2) {
+                // Unless a gene mutated, one gene of each trait comes from each parent, dominant first
+                if [0, 3].contains(&child[at]) && [0, 3].contains(&child[at + 1]) {
+                    assert_eq!((child[at], child[at + 1]), (0, 3));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn kitty_genome_works() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            let dna = Kitties::kitties(0).unwrap().0;
+            assert_eq!(Kitties::kitty_genome(0), Some(genome::decode(&dna)));
+            assert_eq!(Kitties::kitty_genome(1), None);
+        });
+    }
+
+    #[test]
+    fn kitty_breed_tracks_generation() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 1, 0));
+            assert_eq!(Kitties::kitty_breeding(0).generation, 0);
+            assert_eq!(Kitties::kitty_breeding(3).generation, 1);
+            assert_ok!(Kitties::breed(Origin::signed(1), 2, 3, 0));
+            assert_eq!(Kitties::kitty_breeding(4).generation, 2);
+        });
+    }
+
+    #[test]
+    fn kitty_breed_fails_on_cooldown() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 1, 0));
+            assert_eq!(Kitties::kitty_breeding(0).cooldown_until, 15);
+            assert_noop!(
+                Kitties::breed(Origin::signed(1), 0, 2, 0),
+                Error::<Test>::KittyOnCooldown
+            );
+            assert_noop!(
+                Kitties::breed(Origin::signed(1), 2, 1, 0),
+                Error::<Test>::KittyOnCooldown
+            );
+            run_to_block(15);
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 1, 0));
+        });
+    }
+
+    #[test]
+    fn kitty_cooldown_scales_with_generation() {
+        new_test_ext().execute_with(|| {
+            assert_eq!(Kitties::cooldown(0), 5);
+            assert_eq!(Kitties::cooldown(1), 10);
+            assert_eq!(Kitties::cooldown(2), 12);
+            assert_eq!(Kitties::cooldown(u32::max_value()), 12);
+        });
+    }
+
+    #[test]
+    fn kitty_breed_fails_not_valid_owner() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(2)));
+            assert_noop!(
+                Kitties::breed(Origin::signed(2), 0, 1, 0),
+                Error::<Test>::NotValidOwner
+            );
+            assert_noop!(
+                Kitties::breed(Origin::signed(1), 0, 1, 0),
+                Error::<Test>::NoSireOffer
+            );
+        });
+    }
+
+    #[test]
+    fn kitty_breed_with_offered_sire_works() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(2)));
+            assert_ok!(Kitties::offer_sire(Origin::signed(2), 1, 50));
+            assert_noop!(
+                Kitties::breed(Origin::signed(1), 0, 1, 49),
+                Error::<Test>::PriceTooHigh
+            );
+
+            assert_ok!(Kitties::breed(Origin::signed(1), 0, 1, 50));
+            assert_eq!(Kitties::kitty_owner(2), Some(1));
+            assert_eq!(Kitties::kitty_owner(1), Some(2));
+            assert_eq!(Balances::free_balance(1), 500 - 50 - 20);
+            assert_eq!(Balances::free_balance(2), 500 + 50 - 10);
+        });
+    }
+
+    #[test]
+    fn kitty_sire_offer_is_withdrawn() {
+        new_test_ext().execute_with(|| {
+            run_to_block(10);
+            assert_ok!(Kitties::create(Origin::signed(1)));
+            assert_ok!(Kitties::create(Origin::signed(2)));
+            assert_noop!(
+                Kitties::offer_sire(Origin::signed(1), 1, 50),
+                Error::<Test>::NotValidOwner
+            );
+            assert_ok!(Kitties::offer_sire(Origin::signed(2), 1, 50));
+            assert_ok!(Kitties::cancel_sire_offer(Origin::signed(2), 1));
+            assert_eq!(Kitties::sire_offer(1), None);
+
+            // Offers do not survive a change of owner
+            assert_ok!(Kitties::offer_sire(Origin::signed(2), 1, 50));
+            assert_ok!(Kitties::transfer(Origin::signed(2), 3, 1));
+            assert_eq!(Kitties::sire_offer(1), None);
+        });
+    }
+
+    mod migration {
+        use super::*;
+        use frame_support::{Blake2_128Concat, StorageHasher, storage::migration::put_storage_value};
+
+        fn put_v1_list<K: Encode>(item: &[u8], key: K, list: Vec<u32>) {
+            put_storage_value(b"Kitties", item, &Blake2_128Concat::hash(&key.encode()), list);
+        }
+
+        /// Write the state a `V1` chain has after creating kitties 0 and 1 and breeding 2 and 3 from them
+        fn populate_v1() {
+            StorageVersion::put(Releases::V1);
+            for (kitty_id, owner) in [(0u32, 1u64), (1, 1), (2, 1), (3, 2)].iter() {
+                KittyOwner::<Test>::insert(kitty_id, owner);
+            }
+            KittyParents::<Test>::insert(2, (0, 1));
+            KittyParents::<Test>::insert(3, (0, 1));
+            put_v1_list(b"OwnedKitties", 1u64, vec![0, 1, 2]);
+            put_v1_list(b"OwnedKitties", 2u64, vec![3]);
+            put_v1_list(b"KittyChildren", 0u32, vec![2, 3]);
+            put_v1_list(b"KittyChildren", 1u32, vec![2, 3]);
+            put_v1_list(b"KittySiblings", 3u32, vec![2]);
+            // Older versions wrote sibling lists into the partner map
+            put_v1_list(b"KittyPartners", 0u32, vec![1]);
+            put_v1_list(b"KittyPartners", 1u32, vec![0]);
+            put_v1_list(b"KittyPartners", 2u32, vec![3]);
+        }
+
+        #[test]
+        fn migrate_to_v2_works() {
+            new_test_ext().execute_with(|| {
+                populate_v1();
+
+                let expected = migrations::pre_migrate::<Test>().unwrap();
+                assert_eq!(expected, 4);
+                migrations::migrate_to_v2::<Test>();
+                assert_ok!(migrations::post_migrate::<Test>(expected));
+
+                let sorted = |mut ids: Vec<u32>| { ids.sort(); ids };
+                assert_eq!(sorted(Kitties::owned_kitties(&1)), vec![0, 1, 2]);
+                assert_eq!(Kitties::owned_kitties(&2), vec![3]);
+                assert_eq!(Kitties::owned_kitties_count(1), 3);
+                assert_eq!(sorted(Kitties::kitty_children(1)), vec![2, 3]);
+                assert_eq!(Kitties::kitty_children_count(0), 2);
+                assert_eq!(Kitties::kitty_partners(0), vec![1]);
+                assert_eq!(Kitties::kitty_partners(2), Vec::<u32>::new());
+                assert_eq!(Kitties::kitty_siblings(2), vec![3]);
+                assert_eq!(Kitties::kitty_siblings(3), vec![2]);
+            });
+        }
+
+        #[test]
+        fn migrate_to_v2_runs_only_once() {
+            new_test_ext().execute_with(|| {
+                assert_eq!(StorageVersion::get(), Releases::V2);
+                assert!(migrations::pre_migrate::<Test>().is_err());
+                assert_eq!(
+                    migrations::migrate_to_v2::<Test>(),
+                    <Test as frame_system::Trait>::DbWeight::get().reads(1)
+                );
+            });
+        }
+    }
 }
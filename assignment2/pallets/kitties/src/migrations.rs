@@ -0,0 +1,129 @@
+//! Storage migrations for pallet_kitties.
+//!
+//! `migrate_to_v2` runs from `on_runtime_upgrade`. The `pre_migrate` and `post_migrate`
+//! checks are test-only: they bracket it in the migration tests on a hand-built `V1` state.
+
+use super::*;
+use frame_support::{
+    storage::migration::StorageIterator,
+    IterableStorageMap,
+};
+
+const MODULE: &[u8] = b"Kitties";
+const OWNED_KITTIES: &[u8] = b"OwnedKitties";
+const KITTY_SIBLINGS: &[u8] = b"KittySiblings";
+const KITTY_CHILDREN: &[u8] = b"KittyChildren";
+const KITTY_PARTNERS: &[u8] = b"KittyPartners";
+
+/// Drop every entry of a `V1` list map, returning how many there were.
+fn drain_v1<T: Trait>(item: &[u8]) -> u64 {
+    StorageIterator::<Vec<T::KittyIndex>>::new(MODULE, item).drain().count() as u64
+}
+
+/// Move owned kitties and relationships from one `Vec` per key to double maps.
+///
+/// The `V1` lists are dropped rather than converted: `KittyOwner` and `KittyParents` hold the
+/// same information and are what the pallet checks, so the new maps are rebuilt from them. This
+/// also repairs chains where breeding wrote sibling lists into `KittyPartners`. Siblings are no
+/// longer stored, as `kitty_siblings` reads them from `KittyChildren`.
+pub fn migrate_to_v2<T: Trait>() -> Weight {
+    if StorageVersion::get() != Releases::V1 {
+        return T::DbWeight::get().reads(1);
+    }
+
+    // The old and new entries share a prefix, so drop the old ones before writing.
+    let dropped = drain_v1::<T>(OWNED_KITTIES)
+        + drain_v1::<T>(KITTY_SIBLINGS)
+        + drain_v1::<T>(KITTY_CHILDREN)
+        + drain_v1::<T>(KITTY_PARTNERS);
+
+    let mut kitties: u64 = 0;
+    for (kitty_id, owner) in <KittyOwner<T>>::iter() {
+        <OwnedKitties<T>>::insert(&owner, kitty_id, ());
+        <OwnedKittiesCount<T>>::mutate(&owner, |count| *count = count.saturating_add(1));
+        kitties += 1;
+    }
+
+    let mut bred: u64 = 0;
+    for (child, (parent_1, parent_2)) in <KittyParents<T>>::iter() {
+        <KittyChildren<T>>::insert(parent_1, child, ());
+        <KittyChildren<T>>::insert(parent_2, child, ());
+        <KittyChildrenCount<T>>::mutate(parent_1, |count| *count = count.saturating_add(1));
+        <KittyChildrenCount<T>>::mutate(parent_2, |count| *count = count.saturating_add(1));
+        Module::<T>::add_partner(parent_1, parent_2);
+        Module::<T>::add_partner(parent_2, parent_1);
+        bred += 1;
+    }
+
+    StorageVersion::put(Releases::V2);
+
+    T::DbWeight::get().reads_writes(
+        1 + dropped + 2 * kitties + 4 * bred,
+        1 + dropped + 2 * kitties + 8 * bred,
+    )
+}
+
+/// Check the state before `migrate_to_v2`, returning the number of kitties with an owner.
+#[cfg(test)]
+pub fn pre_migrate<T: Trait>() -> Result<u32, &'static str> {
+    if StorageVersion::get() != Releases::V1 {
+        return Err("pallet_kitties is not at storage version V1");
+    }
+    if <OwnedKittiesCount<T>>::iter().next().is_some()
+        || <KittyChildrenCount<T>>::iter().next().is_some()
+        || <KittyPartnersCount<T>>::iter().next().is_some()
+    {
+        return Err("a V1 chain has no count trackers");
+    }
+    Ok(<KittyOwner<T>>::iter().count() as u32)
+}
+
+/// Check the state after `migrate_to_v2` against the kitty count from `pre_migrate`.
+#[cfg(test)]
+pub fn post_migrate<T: Trait>(expected: u32) -> Result<(), &'static str> {
+    if StorageVersion::get() != Releases::V2 {
+        return Err("pallet_kitties was not upgraded to storage version V2");
+    }
+
+    let mut owned = 0u32;
+    for (kitty_id, owner) in <KittyOwner<T>>::iter() {
+        if !<OwnedKitties<T>>::contains_key(&owner, kitty_id) {
+            return Err("migrated kitty is missing from its owner's set");
+        }
+        owned += 1;
+    }
+    if owned != expected {
+        return Err("number of kitties changed during migration");
+    }
+    let mut counted = 0u32;
+    for (owner, count) in <OwnedKittiesCount<T>>::iter() {
+        if Module::<T>::owned_kitties(&owner).len() as u32 != count {
+            return Err("owned kitty count does not match the owner's set");
+        }
+        counted += count;
+    }
+    if counted != expected {
+        return Err("owned kitty counts do not match the migrated kitties");
+    }
+
+    for (child, (parent_1, parent_2)) in <KittyParents<T>>::iter() {
+        if !<KittyChildren<T>>::contains_key(parent_1, child) || !<KittyChildren<T>>::contains_key(parent_2, child) {
+            return Err("migrated kitty is missing from its parents' children");
+        }
+        if !<KittyPartners<T>>::contains_key(parent_1, parent_2) {
+            return Err("parents of a migrated kitty are not partners");
+        }
+    }
+    for (parent, count) in <KittyChildrenCount<T>>::iter() {
+        if Module::<T>::kitty_children(parent).len() as u32 != count {
+            return Err("children counts do not match the migrated kitties");
+        }
+    }
+    for (kitty_id, count) in <KittyPartnersCount<T>>::iter() {
+        if Module::<T>::kitty_partners(kitty_id).len() as u32 != count {
+            return Err("partner counts do not match the migrated kitties");
+        }
+    }
+
+    Ok(())
+}
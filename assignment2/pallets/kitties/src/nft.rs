@@ -0,0 +1,97 @@
+//! A generic interface to non-fungible assets, so other pallets can hold and move kitties
+//! without depending on this pallet's calls and storage.
+//!
+//! Like `Currency`, the functions perform no origin checks: the caller decides who may act for
+//! `owner` or `from`, and the implementation only checks that they do own the token.
+
+use super::*;
+use sp_runtime::traits::UniqueSaturatedInto;
+
+/// A collection of uniquely identified tokens, each owned by a single account.
+pub trait NonFungibleAsset<AccountId> {
+    /// The identifier of a token within the collection
+    type TokenId;
+
+    /// The owner of a token, if it exists
+    fn owner_of(token_id: &Self::TokenId) -> Option<AccountId>;
+
+    /// The number of tokens owned by an account
+    fn balance_of(who: &AccountId) -> u32;
+
+    /// Move a token owned by `from` to `to`, clearing its approval
+    fn transfer(from: &AccountId, to: &AccountId, token_id: &Self::TokenId) -> DispatchResult;
+
+    /// Let `spender` move a token owned by `owner` on their behalf, or clear the approval with `None`
+    fn approve(owner: &AccountId, token_id: &Self::TokenId, spender: Option<AccountId>) -> DispatchResult;
+
+    /// The account approved to move a token, if any
+    fn approved(token_id: &Self::TokenId) -> Option<AccountId>;
+
+    /// The URI of the metadata of a token, if it exists
+    fn token_uri(token_id: &Self::TokenId) -> Option<Vec<u8>>;
+}
+
+impl<T: Trait> NonFungibleAsset<T::AccountId> for Module<T> {
+    type TokenId = T::KittyIndex;
+
+    fn owner_of(kitty_id: &T::KittyIndex) -> Option<T::AccountId> {
+        Self::kitty_owner(kitty_id)
+    }
+
+    fn balance_of(who: &T::AccountId) -> u32 {
+        Self::owned_kitties_count(who)
+    }
+
+    fn transfer(from: &T::AccountId, to: &T::AccountId, kitty_id: &T::KittyIndex) -> DispatchResult {
+        let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+        ensure!(*from == owner, Error::<T>::NotValidOwner);
+        ensure!(from != to, Error::<T>::NotValidReceiver);
+        ensure!(!<Auctions<T>>::contains_key(kitty_id), Error::<T>::KittyInAuction);
+
+        Self::do_transfer(from, to, *kitty_id)?;
+        Self::deposit_event(RawEvent::Transferred(from.clone(), to.clone(), *kitty_id));
+
+        Ok(())
+    }
+
+    fn approve(owner: &T::AccountId, kitty_id: &T::KittyIndex, spender: Option<T::AccountId>) -> DispatchResult {
+        let kitty_owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+        ensure!(*owner == kitty_owner, Error::<T>::NotValidOwner);
+        ensure!(spender.as_ref() != Some(owner), Error::<T>::NotValidReceiver);
+
+        match &spender {
+            Some(spender) => <KittyApprovals<T>>::insert(kitty_id, spender),
+            None => <KittyApprovals<T>>::remove(kitty_id),
+        }
+        Self::deposit_event(RawEvent::ApprovalSet(owner.clone(), *kitty_id, spender));
+
+        Ok(())
+    }
+
+    fn approved(kitty_id: &T::KittyIndex) -> Option<T::AccountId> {
+        Self::kitty_approval(kitty_id)
+    }
+
+    fn token_uri(kitty_id: &T::KittyIndex) -> Option<Vec<u8>> {
+        if !<Kitties<T>>::contains_key(kitty_id) {
+            return None;
+        }
+        let mut uri = T::TokenUriBase::get();
+        uri.extend(decimal((*kitty_id).unique_saturated_into()));
+        Some(uri)
+    }
+}
+
+/// The decimal digits of `n` in ASCII
+fn decimal(mut n: u64) -> Vec<u8> {
+    let mut digits = Vec::new();
+    loop {
+        digits.push(b'0' + (n % 10) as u8);
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    digits.reverse();
+    digits
+}
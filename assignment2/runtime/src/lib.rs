@@ -98,7 +98,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("node-template"),
 	impl_name: create_runtime_str!("node-template"),
 	authoring_version: 1,
-	spec_version: 1,
+	spec_version: 2,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -276,10 +276,24 @@ impl pallet_kitties::Trait for Runtime {
 	type KittyIndex = KittyIndex;
 	type Currency = Balances;
 	type DepositValue = DepositValue;
+	type MaxAuctionsPerBlock = MaxAuctionsPerBlock;
+	type BaseCooldown = BaseCooldown;
+	type MaxCooldown = MaxCooldown;
+	type CommitRevealBreeding = CommitRevealBreeding;
+	type RevealDelay = RevealDelay;
+	type CommitDeposit = CommitDeposit;
+	type TokenUriBase = TokenUriBase;
 }
 
 parameter_types! {
 	pub const DepositValue: u32 = 10;
+	pub const MaxAuctionsPerBlock: u32 = 50;
+	pub const BaseCooldown: BlockNumber = 10 * MINUTES;
+	pub const MaxCooldown: BlockNumber = 7 * DAYS;
+	pub const CommitRevealBreeding: bool = false;
+	pub const RevealDelay: BlockNumber = 3;
+	pub const CommitDeposit: u32 = 10;
+	pub TokenUriBase: Vec<u8> = b"/kitties/".to_vec();
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -299,7 +313,7 @@ construct_runtime!(
 		Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
 		// Include the custom logic from the template pallet in the runtime.
 		TemplateModule: pallet_template::{Module, Call, Storage, Event<T>},
-		KittiesModule: pallet_kitties::{Module, Call, Storage, Event<T>},
+		KittiesModule: pallet_kitties::{Module, Call, Storage, Event<T>, Config<T>},
 	}
 );
 
@@ -460,6 +474,28 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_kitties_runtime_api::KittiesApi<Block, KittyIndex> for Runtime {
+		fn decode_dna(dna: [u8; 16]) -> pallet_kitties::genome::Genome {
+			pallet_kitties::genome::decode(&dna)
+		}
+
+		fn kitty_genome(kitty_id: KittyIndex) -> Option<pallet_kitties::genome::Genome> {
+			KittiesModule::kitty_genome(kitty_id)
+		}
+
+		fn ancestors(kitty_id: KittyIndex, depth: u32) -> Option<pallet_kitties::genealogy::FamilyTree<KittyIndex>> {
+			KittiesModule::ancestors(kitty_id, depth)
+		}
+
+		fn descendants(kitty_id: KittyIndex, depth: u32) -> Option<pallet_kitties::genealogy::FamilyTree<KittyIndex>> {
+			KittiesModule::descendants(kitty_id, depth)
+		}
+
+		fn inbreeding_coefficient(kitty_id: KittyIndex, depth: u32) -> Option<Perbill> {
+			KittiesModule::inbreeding_coefficient(kitty_id, depth)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(